@@ -0,0 +1,130 @@
+use crate::tiles::TilePos;
+use crate::TilemapSize;
+use bevy::utils::HashMap;
+
+/// A broad class of terrain, as chosen by [`classify_biomes`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Biome {
+    Ocean,
+    Beach,
+    Desert,
+    Grassland,
+    Forest,
+    Mountain,
+    Snow,
+}
+
+/// Elevation and moisture thresholds used by [`classify_biomes`].
+///
+/// The elevation thresholds are expected to be in ascending order, as are the moisture
+/// thresholds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BiomeThresholds {
+    /// Tiles below this elevation are [`Biome::Ocean`].
+    pub sea_level: f32,
+    /// Tiles below this elevation, but not below `sea_level`, are [`Biome::Beach`].
+    pub beach_level: f32,
+    /// Tiles at or above this elevation, but below `snow_level`, are [`Biome::Mountain`].
+    pub mountain_level: f32,
+    /// Tiles at or above this elevation are [`Biome::Snow`].
+    pub snow_level: f32,
+    /// Remaining tiles with a moisture below this are [`Biome::Desert`].
+    pub dry_moisture: f32,
+    /// Remaining tiles with a moisture below this, but not below `dry_moisture`, are
+    /// [`Biome::Grassland`]. Wetter tiles are [`Biome::Forest`].
+    pub wet_moisture: f32,
+}
+
+impl Default for BiomeThresholds {
+    fn default() -> Self {
+        Self {
+            sea_level: 0.3,
+            beach_level: 0.35,
+            mountain_level: 0.75,
+            snow_level: 0.9,
+            dry_moisture: 0.3,
+            wet_moisture: 0.6,
+        }
+    }
+}
+
+impl BiomeThresholds {
+    /// Returns the biome of a tile with the given `elevation` and `moisture`.
+    pub fn classify(&self, elevation: f32, moisture: f32) -> Biome {
+        if elevation < self.sea_level {
+            Biome::Ocean
+        } else if elevation < self.beach_level {
+            Biome::Beach
+        } else if elevation >= self.snow_level {
+            Biome::Snow
+        } else if elevation >= self.mountain_level {
+            Biome::Mountain
+        } else if moisture < self.dry_moisture {
+            Biome::Desert
+        } else if moisture < self.wet_moisture {
+            Biome::Grassland
+        } else {
+            Biome::Forest
+        }
+    }
+}
+
+/// Maps each tile of the map to its [`Biome`], as chosen by [`BiomeThresholds::classify`] from its
+/// `elevation` and `moisture`.
+///
+/// Tiles which are missing from either `elevation` or `moisture` are omitted.
+pub fn classify_biomes(
+    elevation: &HashMap<TilePos, f32>,
+    moisture: &HashMap<TilePos, f32>,
+    map_size: &TilemapSize,
+    thresholds: &BiomeThresholds,
+) -> HashMap<TilePos, Biome> {
+    let mut biomes = HashMap::default();
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            let tile_pos = TilePos { x, y };
+            if let (Some(tile_elevation), Some(tile_moisture)) =
+                (elevation.get(&tile_pos), moisture.get(&tile_pos))
+            {
+                biomes.insert(
+                    tile_pos,
+                    thresholds.classify(*tile_elevation, *tile_moisture),
+                );
+            }
+        }
+    }
+    biomes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_biomes_extremes() {
+        let map_size = TilemapSize { x: 4, y: 1 };
+        let tiles: Vec<TilePos> = (0..map_size.x).map(|x| TilePos { x, y: 0 }).collect();
+        // Lowest, highest, and two mid-elevation tiles at the driest and wettest moistures.
+        let elevation: HashMap<TilePos, f32> =
+            tiles.iter().copied().zip([0.0, 1.0, 0.5, 0.5]).collect();
+        let moisture: HashMap<TilePos, f32> =
+            tiles.iter().copied().zip([1.0, 0.0, 0.0, 1.0]).collect();
+
+        let biomes = classify_biomes(
+            &elevation,
+            &moisture,
+            &map_size,
+            &BiomeThresholds::default(),
+        );
+        assert_eq!(biomes[&tiles[0]], Biome::Ocean);
+        assert_eq!(biomes[&tiles[1]], Biome::Snow);
+        assert_eq!(biomes[&tiles[2]], Biome::Desert);
+        assert_eq!(biomes[&tiles[3]], Biome::Forest);
+
+        let mut partial = moisture.clone();
+        partial.remove(&tiles[0]);
+        let biomes = classify_biomes(&elevation, &partial, &map_size, &BiomeThresholds::default());
+        assert!(!biomes.contains_key(&tiles[0]));
+        assert_eq!(biomes.len(), 3);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::tiles::TilePos;
+use crate::TilemapSize;
+use fixedbitset::FixedBitSet;
+
+/// Returns the union of `sets`, such as the visibility bitsets of every unit of a team.
+///
+/// The result is allocated once, with the length of the longest of `sets`.
+pub fn union_bitsets(sets: &[FixedBitSet]) -> FixedBitSet {
+    let len = sets.iter().map(|set| set.len()).max().unwrap_or(0);
+    let mut union = FixedBitSet::with_capacity(len);
+    for set in sets.iter() {
+        union.union_with(set);
+    }
+    union
+}
+
+/// Returns the tiles whose bit differs between `old` and `new`, as a `(newly_set, newly_cleared)`
+/// pair, where bits are indexed by [`TilePos::to_index`].
+///
+/// Tiles are returned in ascending order of their index. Bits whose index lies beyond the tiles
+/// of the map are ignored.
+pub fn bitset_changes(
+    old: &FixedBitSet,
+    new: &FixedBitSet,
+    map_size: &TilemapSize,
+) -> (Vec<TilePos>, Vec<TilePos>) {
+    let mut newly_set = Vec::new();
+    let mut newly_cleared = Vec::new();
+    let mut changed: Vec<usize> = old.symmetric_difference(new).collect();
+    changed.sort_unstable();
+    for index in changed {
+        let tile_pos = match TilePos::from_index(index, map_size) {
+            Some(tile_pos) => tile_pos,
+            None => continue,
+        };
+        if new.contains(index) {
+            newly_set.push(tile_pos);
+        } else {
+            newly_cleared.push(tile_pos);
+        }
+    }
+    (newly_set, newly_cleared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bitset_changes_reports_set_and_cleared_tiles() {
+        let map_size = TilemapSize { x: 3, y: 2 };
+        let mut old = FixedBitSet::with_capacity(8);
+        let mut new = FixedBitSet::with_capacity(8);
+        for index in [0, 1, 4] {
+            old.insert(index);
+        }
+        for index in [1, 2, 5, 7] {
+            new.insert(index);
+        }
+
+        let (newly_set, newly_cleared) = bitset_changes(&old, &new, &map_size);
+        assert_eq!(
+            newly_set,
+            vec![TilePos { x: 2, y: 0 }, TilePos { x: 2, y: 1 }]
+        );
+        assert_eq!(
+            newly_cleared,
+            vec![TilePos { x: 0, y: 0 }, TilePos { x: 1, y: 1 }]
+        );
+    }
+
+    #[test]
+    fn union_bitsets_is_the_set_union() {
+        let mut a = FixedBitSet::with_capacity(4);
+        let mut b = FixedBitSet::with_capacity(8);
+        a.insert(0);
+        a.insert(2);
+        b.insert(2);
+        b.insert(7);
+
+        let union = union_bitsets(&[a, b]);
+        assert_eq!(union.len(), 8);
+        assert_eq!(union.ones().collect::<Vec<usize>>(), vec![0, 2, 7]);
+        assert_eq!(union_bitsets(&[]).len(), 0);
+    }
+}
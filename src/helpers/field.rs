@@ -0,0 +1,426 @@
+use crate::helpers::neighbors::get_neighboring_pos;
+use crate::tiles::TilePos;
+use crate::{TilemapSize, TilemapType};
+use bevy::utils::{HashMap, HashSet};
+
+/// Returns the tiles of the map whose value is greater than the value of every one of their
+/// in-bounds neighbors, as determined by [`get_neighboring_pos`].
+///
+/// If `allow_plateaus` is `true`, then tiles whose value is greater than *or equal to* the value
+/// of every one of their neighbors are returned instead, so that flat peaks are included.
+///
+/// Tiles are returned in row-major order.
+pub fn local_maxima(
+    values: impl Fn(TilePos) -> f32,
+    map_type: &TilemapType,
+    map_size: &TilemapSize,
+    allow_plateaus: bool,
+) -> Vec<TilePos> {
+    let mut maxima = Vec::new();
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            let tile_pos = TilePos { x, y };
+            let value = values(tile_pos);
+            let is_maximum = get_neighboring_pos(&tile_pos, map_size, map_type)
+                .into_iter()
+                .all(|neighbor| {
+                    let neighbor_value = values(neighbor);
+                    if allow_plateaus {
+                        value >= neighbor_value
+                    } else {
+                        value > neighbor_value
+                    }
+                });
+            if is_maximum {
+                maxima.push(tile_pos);
+            }
+        }
+    }
+    maxima
+}
+
+/// Smooths `values` by applying `passes` passes of neighbor averaging, in which each tile's value
+/// is replaced by the average of its own value and the values of its in-bounds neighbors, as
+/// determined by [`get_neighboring_pos`].
+///
+/// Only tiles with an entry in `values` take part: neighbors without an entry are left out of the
+/// average, and no new entries are added. Each pass is computed from the values at the start of
+/// the pass.
+pub fn smooth_field(
+    values: &HashMap<TilePos, f32>,
+    map_type: &TilemapType,
+    map_size: &TilemapSize,
+    passes: u32,
+) -> HashMap<TilePos, f32> {
+    let mut smoothed = values.clone();
+    for _ in 0..passes {
+        smoothed = smoothed
+            .iter()
+            .map(|(tile_pos, value)| {
+                let (sum, count) = get_neighboring_pos(tile_pos, map_size, map_type)
+                    .into_iter()
+                    .filter_map(|neighbor| smoothed.get(&neighbor))
+                    .fold((*value, 1), |(sum, count), neighbor_value| {
+                        (sum + neighbor_value, count + 1)
+                    });
+                (*tile_pos, sum / count as f32)
+            })
+            .collect();
+    }
+    smoothed
+}
+
+/// Returns the tiles of `values` whose value is at or above `threshold`.
+pub fn threshold_field(values: &HashMap<TilePos, f32>, threshold: f32) -> HashSet<TilePos> {
+    values
+        .iter()
+        .filter(|(_, value)| **value >= threshold)
+        .map(|(tile_pos, _)| *tile_pos)
+        .collect()
+}
+
+/// Labels each tile of `mask` with the id of the connected component it belongs to, where tiles
+/// are connected if they are neighbors as determined by [`get_neighboring_pos`].
+///
+/// Ids are consecutive, starting from `0`, and are assigned to components in the row-major order
+/// of their first tile. Tiles of `mask` lying outside of `map_size` are ignored.
+pub fn label_components(
+    mask: &HashSet<TilePos>,
+    map_type: &TilemapType,
+    map_size: &TilemapSize,
+) -> HashMap<TilePos, u32> {
+    let mut labels: HashMap<TilePos, u32> = HashMap::default();
+    let mut next_label = 0;
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            let start = TilePos { x, y };
+            if !mask.contains(&start) || labels.contains_key(&start) {
+                continue;
+            }
+
+            labels.insert(start, next_label);
+            let mut stack = vec![start];
+            while let Some(current) = stack.pop() {
+                for neighbor in get_neighboring_pos(&current, map_size, map_type) {
+                    if mask.contains(&neighbor) && !labels.contains_key(&neighbor) {
+                        labels.insert(neighbor, next_label);
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            next_label += 1;
+        }
+    }
+    labels
+}
+
+/// Returns the tiles of `mask` which have at least one in-bounds neighbor, as determined by
+/// [`get_neighboring_pos`], that is not in `mask`.
+///
+/// If `include_map_edge` is `true`, then tiles of `mask` lying on the outer rows or columns of
+/// the map are also returned, as if the map were surrounded by tiles outside of `mask`. Tiles of
+/// `mask` lying outside of `map_size` are ignored.
+pub fn coastline(
+    mask: &HashSet<TilePos>,
+    map_type: &TilemapType,
+    map_size: &TilemapSize,
+    include_map_edge: bool,
+) -> HashSet<TilePos> {
+    mask.iter()
+        .filter(|tile_pos| tile_pos.within_map_bounds(map_size))
+        .filter(|tile_pos| {
+            let on_map_edge = tile_pos.x == 0
+                || tile_pos.y == 0
+                || tile_pos.x == map_size.x - 1
+                || tile_pos.y == map_size.y - 1;
+            (include_map_edge && on_map_edge)
+                || get_neighboring_pos(tile_pos, map_size, map_type)
+                    .into_iter()
+                    .any(|neighbor| !mask.contains(&neighbor))
+        })
+        .copied()
+        .collect()
+}
+
+/// Maps each in-bounds tile of `mask` to the number of steps to the nearest in-bounds tile which
+/// is not in `mask`, stepping between neighbors as determined by [`get_neighboring_pos`].
+///
+/// Tiles of `mask` which are adjacent to a tile outside of `mask` have a distance of `1`. Tiles
+/// outside of `mask` are not included. If every tile of the map is in `mask`, then the returned
+/// map is empty.
+pub fn distance_to_water(
+    mask: &HashSet<TilePos>,
+    map_type: &TilemapType,
+    map_size: &TilemapSize,
+) -> HashMap<TilePos, u32> {
+    let mut frontier: Vec<TilePos> = Vec::new();
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            let tile_pos = TilePos { x, y };
+            if !mask.contains(&tile_pos) {
+                frontier.push(tile_pos);
+            }
+        }
+    }
+
+    let mut distances: HashMap<TilePos, u32> = HashMap::default();
+    let mut distance = 0;
+    while !frontier.is_empty() {
+        distance += 1;
+        let mut next_frontier = Vec::new();
+        for tile_pos in frontier.iter() {
+            for neighbor in get_neighboring_pos(tile_pos, map_size, map_type) {
+                if mask.contains(&neighbor) && !distances.contains_key(&neighbor) {
+                    distances.insert(neighbor, distance);
+                    next_frontier.push(neighbor);
+                }
+            }
+        }
+        frontier = next_frontier;
+    }
+    distances
+}
+
+/// Returns the tiles at which the influences `a` and `b` are both positive, and differ by at most
+/// `tolerance`, in row-major order. Tiles missing from either map have no influence from it.
+pub fn contested_tiles(
+    a: &HashMap<TilePos, f32>,
+    b: &HashMap<TilePos, f32>,
+    tolerance: f32,
+) -> Vec<TilePos> {
+    let mut tiles: Vec<TilePos> = a
+        .iter()
+        .filter(|(tile_pos, a_value)| match b.get(tile_pos) {
+            Some(b_value) => {
+                **a_value > 0.0 && *b_value > 0.0 && (**a_value - b_value).abs() <= tolerance
+            }
+            None => false,
+        })
+        .map(|(tile_pos, _)| *tile_pos)
+        .collect();
+    tiles.sort_by_key(|tile_pos| (tile_pos.y, tile_pos.x));
+    tiles
+}
+
+/// Returns the tiles which a breadth-first search from all of `sources` at once first reaches at
+/// exactly `depth` steps, stepping between `passable` neighbors as determined by
+/// [`get_neighboring_pos`].
+///
+/// A `depth` of `0` returns the (deduplicated) `sources` themselves, which need not be `passable`.
+/// Obstacles delay tiles behind them, which only appear at the depth of the shortest way around.
+/// Returns an empty vector if no tile is reached at `depth`.
+pub fn bfs_frontier(
+    sources: &[TilePos],
+    depth: u32,
+    passable: impl Fn(TilePos) -> bool,
+    map_type: &TilemapType,
+    map_size: &TilemapSize,
+) -> Vec<TilePos> {
+    let mut visited: HashSet<TilePos> = HashSet::default();
+    let mut frontier: Vec<TilePos> = sources
+        .iter()
+        .filter(|tile_pos| visited.insert(**tile_pos))
+        .copied()
+        .collect();
+
+    for _ in 0..depth {
+        let mut next_frontier = Vec::new();
+        for tile_pos in frontier.iter() {
+            for neighbor in get_neighboring_pos(tile_pos, map_size, map_type) {
+                if passable(neighbor) && visited.insert(neighbor) {
+                    next_frontier.push(neighbor);
+                }
+            }
+        }
+        if next_frontier.is_empty() {
+            return next_frontier;
+        }
+        frontier = next_frontier;
+    }
+    frontier
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::map::HexCoordSystem;
+
+    const MAP_SIZE: TilemapSize = TilemapSize { x: 7, y: 6 };
+    const SQUARE: TilemapType = TilemapType::Square {
+        diagonal_neighbors: false,
+    };
+
+    #[test]
+    fn local_maxima_finds_a_single_peak() {
+        let peak = TilePos { x: 3, y: 2 };
+        let cone = |tile_pos: TilePos| {
+            -((tile_pos.x.abs_diff(peak.x) + tile_pos.y.abs_diff(peak.y)) as f32)
+        };
+        let hex = TilemapType::Hexagon(HexCoordSystem::Row);
+        for map_type in [SQUARE, hex] {
+            assert_eq!(local_maxima(cone, &map_type, &MAP_SIZE, false), vec![peak]);
+            assert_eq!(local_maxima(cone, &map_type, &MAP_SIZE, true), vec![peak]);
+        }
+
+        assert!(local_maxima(|_| 1.0, &SQUARE, &MAP_SIZE, false).is_empty());
+        assert_eq!(
+            local_maxima(|_| 1.0, &SQUARE, &MAP_SIZE, true).len(),
+            MAP_SIZE.count()
+        );
+    }
+
+    #[test]
+    fn smooth_field_spreads_a_spike() {
+        let spike = TilePos { x: 3, y: 2 };
+        let mut values: HashMap<TilePos, f32> = HashMap::default();
+        for y in 0..MAP_SIZE.y {
+            for x in 0..MAP_SIZE.x {
+                values.insert(TilePos { x, y }, 0.0);
+            }
+        }
+        values.insert(spike, 5.0);
+
+        let smoothed = smooth_field(&values, &SQUARE, &MAP_SIZE, 1);
+        assert_eq!(smoothed.len(), values.len());
+        assert_eq!(smoothed[&spike], 1.0);
+        for neighbor in get_neighboring_pos(&spike, &MAP_SIZE, &SQUARE) {
+            assert_eq!(smoothed[&neighbor], 1.0);
+        }
+        // Every tile around the interior spike has the same number of neighbors, so the total is
+        // preserved.
+        let total: f32 = smoothed.values().sum();
+        assert_eq!(total, 5.0);
+    }
+
+    #[test]
+    fn threshold_field_keeps_values_at_or_above() {
+        let values: HashMap<TilePos, f32> = [
+            (TilePos { x: 0, y: 0 }, 0.2),
+            (TilePos { x: 1, y: 0 }, 0.5),
+            (TilePos { x: 2, y: 0 }, 0.8),
+            (TilePos { x: 3, y: 0 }, -1.0),
+        ]
+        .into_iter()
+        .collect();
+
+        let expected: HashSet<TilePos> = [TilePos { x: 1, y: 0 }, TilePos { x: 2, y: 0 }]
+            .into_iter()
+            .collect();
+        assert_eq!(threshold_field(&values, 0.5), expected);
+        assert!(threshold_field(&values, 1.0).is_empty());
+        assert_eq!(threshold_field(&values, -1.0).len(), 4);
+    }
+
+    #[test]
+    fn label_components_gives_distinct_ids() {
+        let left = [TilePos { x: 0, y: 0 }, TilePos { x: 0, y: 1 }];
+        let right = [
+            TilePos { x: 4, y: 3 },
+            TilePos { x: 5, y: 3 },
+            TilePos { x: 5, y: 4 },
+        ];
+        // Only diagonally adjacent to `right`, which does not connect without diagonal neighbors.
+        let lone = TilePos { x: 3, y: 2 };
+        let mask: HashSet<TilePos> = left
+            .iter()
+            .chain(right.iter())
+            .chain([lone].iter())
+            .copied()
+            .collect();
+
+        let labels = label_components(&mask, &SQUARE, &MAP_SIZE);
+        assert_eq!(labels.len(), mask.len());
+        assert!(left.iter().all(|tile_pos| labels[tile_pos] == 0));
+        assert_eq!(labels[&lone], 1);
+        assert!(right.iter().all(|tile_pos| labels[tile_pos] == 2));
+    }
+
+    #[test]
+    fn coastline_is_the_perimeter() {
+        let mut mask: HashSet<TilePos> = HashSet::default();
+        for y in 1..4 {
+            for x in 1..4 {
+                mask.insert(TilePos { x, y });
+            }
+        }
+
+        let interior = TilePos { x: 2, y: 2 };
+        let mut expected = mask.clone();
+        expected.remove(&interior);
+        assert_eq!(coastline(&mask, &SQUARE, &MAP_SIZE, false), expected);
+
+        // The whole map has no neighbors outside of the mask, only its outer rows and columns.
+        let full: HashSet<TilePos> = (0..MAP_SIZE.y)
+            .flat_map(|y| (0..MAP_SIZE.x).map(move |x| TilePos { x, y }))
+            .collect();
+        assert!(coastline(&full, &SQUARE, &MAP_SIZE, false).is_empty());
+        let edge = coastline(&full, &SQUARE, &MAP_SIZE, true);
+        assert_eq!(edge.len() as u32, 2 * (MAP_SIZE.x + MAP_SIZE.y) - 4);
+        assert!(!edge.contains(&interior));
+    }
+
+    #[test]
+    fn distance_to_water_grows_inland() {
+        // Land everywhere except the left column.
+        let mask: HashSet<TilePos> = (0..MAP_SIZE.y)
+            .flat_map(|y| (1..MAP_SIZE.x).map(move |x| TilePos { x, y }))
+            .collect();
+
+        let distances = distance_to_water(&mask, &SQUARE, &MAP_SIZE);
+        assert_eq!(distances.len(), mask.len());
+        for (tile_pos, distance) in distances.iter() {
+            assert_eq!(*distance, tile_pos.x);
+        }
+        assert!(distances[&TilePos { x: 5, y: 2 }] > distances[&TilePos { x: 1, y: 2 }]);
+
+        let full: HashSet<TilePos> = mask
+            .into_iter()
+            .chain((0..MAP_SIZE.y).map(|y| TilePos { x: 0, y }))
+            .collect();
+        assert!(distance_to_water(&full, &SQUARE, &MAP_SIZE).is_empty());
+    }
+
+    #[test]
+    fn contested_tiles_form_a_band() {
+        let mut a: HashMap<TilePos, f32> = HashMap::default();
+        let mut b: HashMap<TilePos, f32> = HashMap::default();
+        for y in 0..MAP_SIZE.y {
+            for x in 0..MAP_SIZE.x {
+                a.insert(TilePos { x, y }, (MAP_SIZE.x - 1 - x) as f32);
+                b.insert(TilePos { x, y }, x as f32);
+            }
+        }
+
+        let band: Vec<TilePos> = (0..MAP_SIZE.y).map(|y| TilePos { x: 3, y }).collect();
+        assert_eq!(contested_tiles(&a, &b, 1.0), band);
+        let wide_band: Vec<TilePos> = (0..MAP_SIZE.y)
+            .flat_map(|y| (2..=4).map(move |x| TilePos { x, y }))
+            .collect();
+        assert_eq!(contested_tiles(&a, &b, 2.0), wide_band);
+        // Tiles without influence from either side are never contested: `a` has none in the last
+        // column, and `b` has none in the first, so only the columns in between remain.
+        assert_eq!(
+            contested_tiles(&a, &b, 100.0).len(),
+            ((MAP_SIZE.x - 2) * MAP_SIZE.y) as usize
+        );
+    }
+
+    #[test]
+    fn bfs_frontier_is_delayed_by_obstacles() {
+        let source = TilePos { x: 0, y: 2 };
+        let behind = TilePos { x: 3, y: 2 };
+        // A wall along `x == 2`, with a gap in the top row.
+        let passable = |tile_pos: TilePos| tile_pos.x != 2 || tile_pos.y == MAP_SIZE.y - 1;
+        let frontier = |depth, passable: &dyn Fn(TilePos) -> bool| {
+            bfs_frontier(&[source, source], depth, passable, &SQUARE, &MAP_SIZE)
+        };
+
+        assert_eq!(frontier(0, &passable), vec![source]);
+        assert!(frontier(3, &|_| true).contains(&behind));
+        assert!(!frontier(3, &passable).contains(&behind));
+        assert!(frontier(9, &passable).contains(&behind));
+        assert!((0..9).all(|depth| !frontier(depth, &passable).contains(&behind)));
+        assert!(frontier(100, &passable).is_empty());
+    }
+}
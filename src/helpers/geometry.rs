@@ -1,5 +1,11 @@
-use crate::{TilemapGridSize, TilemapSize, Transform};
-use bevy::math::Vec2;
+use crate::helpers::hex_grid::axial::AxialPos;
+use crate::helpers::hex_grid::neighbors::HEX_OFFSETS;
+use crate::helpers::iso_grid::diamond::DiamondPos;
+use crate::helpers::iso_grid::staggered::StaggeredPos;
+use crate::map::{HexCoordSystem, IsoCoordSystem};
+use crate::tiles::TilePos;
+use crate::{TilemapGridSize, TilemapSize, TilemapType, Transform};
+use bevy::math::{UVec2, Vec2};
 
 /// Calculates a [`Vec2`] position for a tilemap so that when set to this position, it shows up
 /// centered on the screen.
@@ -20,3 +26,178 @@ pub fn get_tilemap_center_transform(
     let center = get_tilemap_center(size, grid_size);
     Transform::from_xyz(center.x, center.y, z)
 }
+
+/// Splits the rectangular region spanning from `min` to `max` (inclusive) into chunks of at most
+/// `chunk_size` tiles, returning the inclusive `(min, max)` corners of each chunk.
+///
+/// Chunks are returned row by row, starting from `min`. Chunks along the far edges of the region
+/// are clamped to the region, so they may be smaller than `chunk_size`. Together, the chunks cover
+/// the region exactly, without overlapping.
+///
+/// Returns an empty `Vec` if `min` is greater than `max` along either axis.
+///
+/// Panics if either component of `chunk_size` is `0`.
+pub fn partition_region(min: TilePos, max: TilePos, chunk_size: UVec2) -> Vec<(TilePos, TilePos)> {
+    assert!(
+        chunk_size.x > 0 && chunk_size.y > 0,
+        "chunk_size must be non-zero, got {}",
+        chunk_size
+    );
+
+    let mut chunks = Vec::new();
+    if min.x > max.x || min.y > max.y {
+        return chunks;
+    }
+
+    for y in (min.y..=max.y).step_by(chunk_size.y as usize) {
+        for x in (min.x..=max.x).step_by(chunk_size.x as usize) {
+            let chunk_min = TilePos { x, y };
+            let chunk_max = TilePos {
+                x: (x + (chunk_size.x - 1)).min(max.x),
+                y: (y + (chunk_size.y - 1)).min(max.y),
+            };
+            chunks.push((chunk_min, chunk_max));
+        }
+    }
+    chunks
+}
+
+/// Returns the world space length of the edge shared by tiles `a` and `b`, or `None` if they do
+/// not share an edge.
+///
+/// Square and isometric tiles share an edge with their four orthogonal neighbors, but only a
+/// corner with their diagonal neighbors. Hexagonal tiles share an edge with each of their six
+/// neighbors, as laid out by [`AxialPos::corners_in_world`].
+pub fn shared_edge_length(
+    a: TilePos,
+    b: TilePos,
+    grid_size: &TilemapGridSize,
+    map_type: &TilemapType,
+) -> Option<f32> {
+    match map_type {
+        TilemapType::Square { .. } => {
+            let (dx, dy) = (a.x.abs_diff(b.x), a.y.abs_diff(b.y));
+            match (dx, dy) {
+                (1, 0) => Some(grid_size.y),
+                (0, 1) => Some(grid_size.x),
+                _ => None,
+            }
+        }
+        TilemapType::Isometric { coord_system, .. } => {
+            let (a, b) = match coord_system {
+                IsoCoordSystem::Diamond => (DiamondPos::from(&a), DiamondPos::from(&b)),
+                IsoCoordSystem::Staggered => (
+                    DiamondPos::from(&StaggeredPos::from(&a)),
+                    DiamondPos::from(&StaggeredPos::from(&b)),
+                ),
+            };
+            // Tiles are rhombi, so the edge shared with a neighbor along one axis is parallel to,
+            // and as long as, the step to a neighbor along the other axis.
+            let origin = DiamondPos { x: 0, y: 0 }.center_in_world(grid_size);
+            match ((a.x - b.x).abs(), (a.y - b.y).abs()) {
+                (1, 0) => Some(
+                    DiamondPos { x: 0, y: 1 }
+                        .center_in_world(grid_size)
+                        .distance(origin),
+                ),
+                (0, 1) => Some(
+                    DiamondPos { x: 1, y: 0 }
+                        .center_in_world(grid_size)
+                        .distance(origin),
+                ),
+                _ => None,
+            }
+        }
+        TilemapType::Hexagon(hex_coord_sys) => {
+            let a = AxialPos::from_tile_pos_given_coord_system(&a, *hex_coord_sys);
+            let b = AxialPos::from_tile_pos_given_coord_system(&b, *hex_coord_sys);
+            let direction = HEX_OFFSETS.iter().position(|offset| a + *offset == b)?;
+            // For row oriented hexes, the edge facing direction `i` runs from corner `i - 1` to
+            // corner `i`, while for column oriented hexes, it runs from corner `i` to `i + 1`.
+            let (start, end) = match hex_coord_sys {
+                HexCoordSystem::Row | HexCoordSystem::RowEven | HexCoordSystem::RowOdd => {
+                    ((direction + 5) % 6, direction)
+                }
+                HexCoordSystem::Column | HexCoordSystem::ColumnEven | HexCoordSystem::ColumnOdd => {
+                    (direction, (direction + 1) % 6)
+                }
+            };
+            let corners = a.corners_in_world(grid_size, *hex_coord_sys);
+            Some(corners[start].distance(corners[end]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partition_region_covers_without_overlap_or_gap() {
+        let (min, max) = (TilePos { x: 2, y: 1 }, TilePos { x: 11, y: 7 });
+        let chunks = partition_region(min, max, UVec2::new(4, 3));
+
+        let mut covered = vec![0; 16 * 16];
+        for (chunk_min, chunk_max) in chunks.iter() {
+            assert!(chunk_max.x - chunk_min.x < 4 && chunk_max.y - chunk_min.y < 3);
+            for y in chunk_min.y..=chunk_max.y {
+                for x in chunk_min.x..=chunk_max.x {
+                    covered[(y * 16 + x) as usize] += 1;
+                }
+            }
+        }
+        for y in 0..16 {
+            for x in 0..16 {
+                let inside = (min.x..=max.x).contains(&x) && (min.y..=max.y).contains(&y);
+                assert_eq!(covered[(y * 16 + x) as usize], inside as u32);
+            }
+        }
+        assert!(partition_region(max, min, UVec2::new(4, 3)).is_empty());
+    }
+
+    #[test]
+    fn shared_edge_length_of_square_and_hex_tiles() {
+        let square = TilemapType::Square {
+            diagonal_neighbors: true,
+        };
+        let grid_size = TilemapGridSize { x: 16.0, y: 12.0 };
+        let tile = TilePos { x: 3, y: 3 };
+        let length = |x, y| shared_edge_length(tile, TilePos { x, y }, &grid_size, &square);
+        assert_eq!(length(4, 3), Some(12.0));
+        assert_eq!(length(3, 2), Some(16.0));
+        assert_eq!(length(4, 4), None);
+        assert_eq!(length(5, 3), None);
+
+        // Regular hexes, whose edges are all as long as their circumradius.
+        let side = 8.0;
+        for hex_coord_sys in [HexCoordSystem::Row, HexCoordSystem::Column] {
+            let grid_size = match hex_coord_sys {
+                HexCoordSystem::Row => TilemapGridSize {
+                    x: 3f32.sqrt() * side,
+                    y: 2.0 * side,
+                },
+                _ => TilemapGridSize {
+                    x: 2.0 * side,
+                    y: 3f32.sqrt() * side,
+                },
+            };
+            let map_type = TilemapType::Hexagon(hex_coord_sys);
+            let tile_pos = |axial_pos: AxialPos| {
+                axial_pos
+                    .as_tile_pos_given_coord_system(&TilemapSize { x: 8, y: 8 }, hex_coord_sys)
+                    .unwrap()
+            };
+            let center = AxialPos { q: 3, r: 3 };
+            for neighbor in center.neighbors() {
+                let length =
+                    shared_edge_length(tile_pos(center), tile_pos(neighbor), &grid_size, &map_type);
+                assert!((length.unwrap() - side).abs() < 1e-3);
+            }
+            let far = center + AxialPos { q: 2, r: 0 };
+            assert_eq!(
+                shared_edge_length(tile_pos(center), tile_pos(far), &grid_size, &map_type),
+                None
+            );
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use crate::helpers::hex_grid::consts::{DOUBLE_INV_SQRT_3, HALF_SQRT_3, INV_SQRT_3};
 use crate::helpers::hex_grid::cube::{CubePos, FractionalCubePos};
 use crate::helpers::hex_grid::offset::{ColEvenPos, ColOddPos, RowEvenPos, RowOddPos};
+use crate::map::HexCoordSystem;
 use crate::tiles::TilePos;
 use crate::{TilemapGridSize, TilemapSize};
 use bevy::math::{Mat2, Vec2};
@@ -199,6 +200,31 @@ impl AxialPos {
         (*self - *other).magnitude()
     }
 
+    /// Returns the hex_grid distance between `self` and `other` on a map which wraps around
+    /// horizontally, so that the column `q = map_size.x` is the same as the column `q = 0`.
+    ///
+    /// Both the direct path, and the paths crossing the seam in either direction, are considered;
+    /// the shortest distance is returned. Only the column (`q`) axis wraps.
+    pub fn distance_wrapped(&self, other: &AxialPos, map_size: &TilemapSize) -> i32 {
+        let width = map_size.x as i32;
+        [-width, 0, width]
+            .iter()
+            .map(|&shift| {
+                self.distance_from(&AxialPos {
+                    q: other.q + shift,
+                    r: other.r,
+                })
+            })
+            .min()
+            .unwrap()
+    }
+
+    /// Returns the color (`0`, `1`, or `2`) of `self` in a proper three-coloring of the hex grid:
+    /// no two adjacent hexes share the same color.
+    pub fn three_color(&self) -> u8 {
+        (self.q - self.r).rem_euclid(3) as u8
+    }
+
     /// Returns the center of the hex_grid in world space, assuming that:
     ///     1) tiles are row-oriented ("pointy top"),
     ///     2) the center of the hex_grid with index `(0, 0)` is located at `[0.0, 0.0]`.
@@ -221,6 +247,104 @@ impl AxialPos {
         )
     }
 
+    /// Returns the center of the hex_grid in world space, using
+    /// [`center_in_world_row`](AxialPos::center_in_world_row) for row-oriented coordinate systems
+    /// and [`center_in_world_col`](AxialPos::center_in_world_col) for column-oriented ones.
+    pub fn center_in_world_given_coord_system(
+        &self,
+        grid_size: &TilemapGridSize,
+        hex_coord_sys: HexCoordSystem,
+    ) -> Vec2 {
+        match hex_coord_sys {
+            HexCoordSystem::Row | HexCoordSystem::RowEven | HexCoordSystem::RowOdd => {
+                self.center_in_world_row(grid_size)
+            }
+            HexCoordSystem::Column | HexCoordSystem::ColumnEven | HexCoordSystem::ColumnOdd => {
+                self.center_in_world_col(grid_size)
+            }
+        }
+    }
+
+    /// Returns the angle, in radians, of the vector from the world space center of `self` to that
+    /// of `other`, measured counter-clockwise from the positive `x` axis, in the range
+    /// `[-pi, pi]`.
+    ///
+    /// Returns `0.0` if `self` and `other` are the same hex.
+    pub fn bearing_to(
+        &self,
+        other: &AxialPos,
+        grid_size: &TilemapGridSize,
+        hex_coord_sys: HexCoordSystem,
+    ) -> f32 {
+        let delta = other.center_in_world_given_coord_system(grid_size, hex_coord_sys)
+            - self.center_in_world_given_coord_system(grid_size, hex_coord_sys);
+        delta.y.atan2(delta.x)
+    }
+
+    /// Returns the straight line distance between the world space centers of `self` and `other`.
+    ///
+    /// Unlike [`distance_from`](AxialPos::distance_from), which counts hex steps, this is the
+    /// Euclidean distance in world units.
+    pub fn world_distance(
+        &self,
+        other: &AxialPos,
+        grid_size: &TilemapGridSize,
+        hex_coord_sys: HexCoordSystem,
+    ) -> f32 {
+        self.center_in_world_given_coord_system(grid_size, hex_coord_sys)
+            .distance(other.center_in_world_given_coord_system(grid_size, hex_coord_sys))
+    }
+
+    /// Returns the six corners of this hex in world space, in counter-clockwise order.
+    ///
+    /// Corners are derived from the same layout as
+    /// [`center_in_world_given_coord_system`](AxialPos::center_in_world_given_coord_system): row
+    /// oriented hexes are `grid_size.x` wide and `grid_size.y` tall with a corner pointing up,
+    /// while column oriented hexes have an edge at the top instead.
+    pub fn corners_in_world(
+        &self,
+        grid_size: &TilemapGridSize,
+        hex_coord_sys: HexCoordSystem,
+    ) -> [Vec2; 6] {
+        let center = self.center_in_world_given_coord_system(grid_size, hex_coord_sys);
+        let (half_x, half_y) = (grid_size.x / 2.0, grid_size.y / 2.0);
+        let offsets = match hex_coord_sys {
+            HexCoordSystem::Row | HexCoordSystem::RowEven | HexCoordSystem::RowOdd => [
+                Vec2::new(half_x, half_y / 2.0),
+                Vec2::new(0.0, half_y),
+                Vec2::new(-half_x, half_y / 2.0),
+                Vec2::new(-half_x, -half_y / 2.0),
+                Vec2::new(0.0, -half_y),
+                Vec2::new(half_x, -half_y / 2.0),
+            ],
+            HexCoordSystem::Column | HexCoordSystem::ColumnEven | HexCoordSystem::ColumnOdd => [
+                Vec2::new(half_x, 0.0),
+                Vec2::new(half_x / 2.0, half_y),
+                Vec2::new(-half_x / 2.0, half_y),
+                Vec2::new(-half_x, 0.0),
+                Vec2::new(-half_x / 2.0, -half_y),
+                Vec2::new(half_x / 2.0, -half_y),
+            ],
+        };
+        offsets.map(|offset| center + offset)
+    }
+
+    /// Returns `true` if the center of this hex lies within the rectangle centered at
+    /// `rect_center` with the given `half_extents`, rotated counter-clockwise by `rotation`
+    /// radians about its center.
+    pub fn in_oriented_rect(
+        &self,
+        rect_center: Vec2,
+        half_extents: Vec2,
+        rotation: f32,
+        grid_size: &TilemapGridSize,
+        hex_coord_sys: HexCoordSystem,
+    ) -> bool {
+        let world_pos = self.center_in_world_given_coord_system(grid_size, hex_coord_sys);
+        let local_pos = Mat2::from_angle(-rotation) * (world_pos - rect_center);
+        local_pos.x.abs() <= half_extents.x && local_pos.y.abs() <= half_extents.y
+    }
+
     /// Returns the axial position of the hex_grid containing the given world position, assuming that:
     ///     1) tiles are row-oriented ("pointy top") and that
     ///     2) the world position corresponding to `[0.0, 0.0]` lies in the hex_grid indexed `(0, 0)`.
@@ -252,6 +376,38 @@ impl AxialPos {
     pub fn as_tile_pos(&self, map_size: &TilemapSize) -> Option<TilePos> {
         TilePos::from_i32_pair(self.q, self.r, map_size)
     }
+
+    /// Returns the axial position of the tile at `tile_pos`, where `tile_pos` is interpreted
+    /// according to the given `hex_coord_sys`.
+    pub fn from_tile_pos_given_coord_system(
+        tile_pos: &TilePos,
+        hex_coord_sys: HexCoordSystem,
+    ) -> AxialPos {
+        match hex_coord_sys {
+            HexCoordSystem::RowEven => RowEvenPos::from(tile_pos).into(),
+            HexCoordSystem::RowOdd => RowOddPos::from(tile_pos).into(),
+            HexCoordSystem::ColumnEven => ColEvenPos::from(tile_pos).into(),
+            HexCoordSystem::ColumnOdd => ColOddPos::from(tile_pos).into(),
+            HexCoordSystem::Row | HexCoordSystem::Column => AxialPos::from(tile_pos),
+        }
+    }
+
+    /// Try converting into a [`TilePos`] expressed in the given `hex_coord_sys`.
+    ///
+    /// Returns `None` if the resulting tile position lies out of the bounds of `map_size`.
+    pub fn as_tile_pos_given_coord_system(
+        &self,
+        map_size: &TilemapSize,
+        hex_coord_sys: HexCoordSystem,
+    ) -> Option<TilePos> {
+        match hex_coord_sys {
+            HexCoordSystem::RowEven => RowEvenPos::from(*self).as_tile_pos(map_size),
+            HexCoordSystem::RowOdd => RowOddPos::from(*self).as_tile_pos(map_size),
+            HexCoordSystem::ColumnEven => ColEvenPos::from(*self).as_tile_pos(map_size),
+            HexCoordSystem::ColumnOdd => ColOddPos::from(*self).as_tile_pos(map_size),
+            HexCoordSystem::Row | HexCoordSystem::Column => self.as_tile_pos(map_size),
+        }
+    }
 }
 
 /// A fractional axial position can represent a point that lies inside a hexagon. It is typically
@@ -265,11 +421,28 @@ pub struct FractionalAxialPos {
 }
 
 impl FractionalAxialPos {
-    fn round(&self) -> AxialPos {
+    /// Returns `self` rounded to the [`AxialPos`] of the hex that contains it.
+    pub fn round(&self) -> AxialPos {
         let frac_cube_pos = FractionalCubePos::from(*self);
         let cube_pos = frac_cube_pos.round();
         cube_pos.into()
     }
+
+    /// Returns the hex_grid distance between `self` and `other`.
+    pub fn distance_from(&self, other: &FractionalAxialPos) -> f32 {
+        let dq = self.q - other.q;
+        let dr = self.r - other.r;
+        dq.abs().max(dr.abs()).max((dq + dr).abs())
+    }
+}
+
+impl From<AxialPos> for FractionalAxialPos {
+    fn from(axial_pos: AxialPos) -> Self {
+        FractionalAxialPos {
+            q: axial_pos.q as f32,
+            r: axial_pos.r as f32,
+        }
+    }
 }
 
 impl From<Vec2> for FractionalAxialPos {
@@ -277,3 +450,88 @@ impl From<Vec2> for FractionalAxialPos {
         FractionalAxialPos { q: v.x, r: v.y }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    #[test]
+    fn in_oriented_rect_rotated_by_45_degrees() {
+        // With these dimensions, the hexes `(0, r)` lie on the diagonal `y = x`.
+        let grid_size = TilemapGridSize { x: 24.0, y: 16.0 };
+        let (center, half_extents) = (Vec2::new(12.0, 12.0), Vec2::new(20.0, 2.0));
+        let inside = |axial_pos: AxialPos| {
+            axial_pos.in_oriented_rect(
+                center,
+                half_extents,
+                FRAC_PI_4,
+                &grid_size,
+                HexCoordSystem::Row,
+            )
+        };
+
+        assert!(inside(AxialPos { q: 0, r: 0 }));
+        assert!(inside(AxialPos { q: 0, r: 1 }));
+        assert!(inside(AxialPos { q: 0, r: 2 }));
+        assert!(!inside(AxialPos { q: 0, r: 3 }));
+        assert!(!inside(AxialPos { q: 1, r: 0 }));
+        assert!(!inside(AxialPos { q: -1, r: 2 }));
+    }
+
+    #[test]
+    fn three_color_differs_from_every_neighbor() {
+        for axial_pos in (AxialPos { q: 0, r: 0 }).range(5) {
+            for neighbor in axial_pos.neighbors() {
+                assert_ne!(axial_pos.three_color(), neighbor.three_color());
+            }
+        }
+    }
+
+    #[test]
+    fn distance_wrapped_across_the_seam() {
+        let map_size = TilemapSize { x: 10, y: 6 };
+        let (west, east) = (AxialPos { q: 0, r: 3 }, AxialPos { q: 9, r: 3 });
+        assert_eq!(west.distance_from(&east), 9);
+        assert_eq!(west.distance_wrapped(&east, &map_size), 1);
+        assert_eq!(east.distance_wrapped(&west, &map_size), 1);
+
+        let near = AxialPos { q: 2, r: 2 };
+        assert_eq!(
+            west.distance_wrapped(&near, &map_size),
+            west.distance_from(&near)
+        );
+    }
+
+    #[test]
+    fn bearing_to_cardinal_neighbors() {
+        let grid_size = TilemapGridSize { x: 16.0, y: 16.0 };
+        let origin = AxialPos { q: 0, r: 0 };
+        let bearing =
+            |q, r, hex_coord_sys| origin.bearing_to(&AxialPos { q, r }, &grid_size, hex_coord_sys);
+
+        assert!(bearing(1, 0, HexCoordSystem::Row).abs() < 1e-6);
+        assert!((bearing(-1, 0, HexCoordSystem::Row).abs() - PI).abs() < 1e-6);
+        assert!((bearing(0, 1, HexCoordSystem::Column) - FRAC_PI_2).abs() < 1e-6);
+        assert!((bearing(0, -1, HexCoordSystem::Column) + FRAC_PI_2).abs() < 1e-6);
+        assert_eq!(bearing(0, 0, HexCoordSystem::Row), 0.0);
+    }
+
+    #[test]
+    fn world_distance_between_adjacent_hexes() {
+        let grid_size = TilemapGridSize { x: 16.0, y: 16.0 };
+        let origin = AxialPos { q: 0, r: 0 };
+        // Row hexes are laid out `grid_size.x` apart along a row, and rows are `0.75 *
+        // grid_size.y` apart, shifted by half a hex.
+        let east = origin.world_distance(&AxialPos { q: 1, r: 0 }, &grid_size, HexCoordSystem::Row);
+        assert!((east - 16.0).abs() < 1e-4);
+        let north_east =
+            origin.world_distance(&AxialPos { q: 0, r: 1 }, &grid_size, HexCoordSystem::Row);
+        assert!((north_east - (8.0f32 * 8.0 + 12.0 * 12.0).sqrt()).abs() < 1e-4);
+        assert_eq!(
+            origin.world_distance(&origin, &grid_size, HexCoordSystem::Row),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use crate::helpers::hex_grid::axial::AxialPos;
+use crate::helpers::hex_grid::neighbors::{HexDirection, HEX_OFFSETS};
+
+/// Identifies the edge shared by two adjacent hexes.
+///
+/// An `EdgeId` is undirected: the edge between `a` and `b` is the same as the edge between `b`
+/// and `a`. Internally, the two hexes are stored in sorted order, so that equal edges compare and
+/// hash equally.
+#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct EdgeId {
+    a: AxialPos,
+    b: AxialPos,
+}
+
+impl EdgeId {
+    /// Returns the edge between `a` and `b`.
+    ///
+    /// Panics if `a` and `b` are not adjacent.
+    pub fn new(a: AxialPos, b: AxialPos) -> EdgeId {
+        assert_eq!(
+            a.distance_from(&b),
+            1,
+            "{:?} and {:?} are not adjacent hexes",
+            a,
+            b
+        );
+        if a <= b {
+            EdgeId { a, b }
+        } else {
+            EdgeId { a: b, b: a }
+        }
+    }
+
+    /// Returns the edge of `tile` which faces its neighbor in the given `direction`.
+    pub fn from_direction(tile: AxialPos, direction: HexDirection) -> EdgeId {
+        EdgeId::new(tile, tile + HEX_OFFSETS[direction as usize])
+    }
+
+    /// Returns the two hexes sharing this edge, in sorted order.
+    pub fn tiles(&self) -> (AxialPos, AxialPos) {
+        (self.a, self.b)
+    }
+}
+
+/// Identifies a vertex of the hex grid, i.e. a corner shared by three hexes.
+///
+/// Internally, the three hexes sharing the vertex are stored in sorted order, so that the same
+/// corner, obtained from any one of the hexes sharing it, compares and hashes equally.
+#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct VertexId {
+    tiles: [AxialPos; 3],
+}
+
+impl VertexId {
+    /// Returns the vertex shared by `tile` and its neighbors in directions `a` and `b`.
+    ///
+    /// Panics if `a` and `b` are not adjacent directions.
+    pub fn new(tile: AxialPos, a: HexDirection, b: HexDirection) -> VertexId {
+        assert!(
+            a + 1 == b || b + 1 == a,
+            "{:?} and {:?} are not adjacent directions",
+            a,
+            b
+        );
+        let mut tiles = [
+            tile,
+            tile + HEX_OFFSETS[a as usize],
+            tile + HEX_OFFSETS[b as usize],
+        ];
+        tiles.sort();
+        VertexId { tiles }
+    }
+
+    /// Returns the three hexes sharing this vertex, in sorted order.
+    pub fn tiles(&self) -> [AxialPos; 3] {
+        self.tiles
+    }
+}
@@ -0,0 +1,931 @@
+use crate::helpers::hex_grid::axial::{AxialPos, FractionalAxialPos};
+use crate::helpers::hex_grid::neighbors::{HexDirection, HEX_DIRECTIONS, HEX_OFFSETS};
+use crate::map::HexCoordSystem;
+use crate::tiles::TilePos;
+use crate::{TilemapGridSize, TilemapSize};
+use bevy::math::Vec2;
+use bevy::utils::{HashMap, HashSet};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+/// Returns the value at `at` interpolated from `samples` by inverse distance weighting, using hex
+/// distance.
+///
+/// Each sample is weighted by `1 / d^power`, where `d` is its hex distance from `at`. If `at`
+/// coincides with a sample, then that sample's value is returned directly.
+///
+/// Returns `0.0` if `samples` is empty.
+pub fn idw_sample(at: FractionalAxialPos, samples: &[(AxialPos, f32)], power: f32) -> f32 {
+    let mut weighted_sum = 0.0;
+    let mut total_weight = 0.0;
+    for (tile, value) in samples.iter() {
+        let distance = at.distance_from(&FractionalAxialPos::from(*tile));
+        if distance <= f32::EPSILON {
+            return *value;
+        }
+        let weight = distance.powf(-power);
+        weighted_sum += weight * value;
+        total_weight += weight;
+    }
+
+    if total_weight == 0.0 {
+        0.0
+    } else {
+        weighted_sum / total_weight
+    }
+}
+
+/// Returns the direction of the lowest in-bounds neighbor of `tile_pos`, if that neighbor is
+/// strictly lower than `tile_pos` itself.
+fn downhill_direction(
+    tile_pos: TilePos,
+    height: &impl Fn(TilePos) -> f32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> Option<HexDirection> {
+    let axial_pos = AxialPos::from_tile_pos_given_coord_system(&tile_pos, hex_coord_sys);
+    let mut lowest = None;
+    let mut lowest_height = height(tile_pos);
+    for (direction, offset) in HEX_DIRECTIONS.iter().zip(HEX_OFFSETS.iter()) {
+        if let Some(neighbor) =
+            (axial_pos + *offset).as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+        {
+            let neighbor_height = height(neighbor);
+            if neighbor_height < lowest_height {
+                lowest = Some(*direction);
+                lowest_height = neighbor_height;
+            }
+        }
+    }
+    lowest
+}
+
+/// Maps each tile of the map to the direction of its lowest in-bounds neighbor, or to `None` if
+/// no neighbor is strictly lower than the tile itself (the tile is a sink). If several neighbors
+/// are equally low, the first one in [`HexDirection`] order is chosen.
+pub fn flow_directions(
+    height: impl Fn(TilePos) -> f32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashMap<TilePos, Option<HexDirection>> {
+    let mut directions = HashMap::default();
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            let tile_pos = TilePos { x, y };
+            directions.insert(
+                tile_pos,
+                downhill_direction(tile_pos, &height, hex_coord_sys, map_size),
+            );
+        }
+    }
+    directions
+}
+
+/// Maps each tile of the map to its drainage area: the number of tiles, including itself, whose
+/// flow passes through it.
+///
+/// Flow follows [`flow_directions`]: tiles are processed in descending order of height, and each
+/// one passes its accumulated flow on to its lowest neighbor. Sinks keep all of the flow which
+/// reaches them.
+pub fn flow_accumulation(
+    height: impl Fn(TilePos) -> f32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashMap<TilePos, u32> {
+    let mut tiles: Vec<(TilePos, f32)> = Vec::with_capacity(map_size.count());
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            let tile_pos = TilePos { x, y };
+            tiles.push((tile_pos, height(tile_pos)));
+        }
+    }
+    tiles.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+    let mut accumulation: HashMap<TilePos, u32> =
+        tiles.iter().map(|(tile_pos, _)| (*tile_pos, 1)).collect();
+    for (tile_pos, _) in tiles.iter() {
+        if let Some(direction) = downhill_direction(*tile_pos, &height, hex_coord_sys, map_size) {
+            let axial_pos = AxialPos::from_tile_pos_given_coord_system(tile_pos, hex_coord_sys);
+            let downhill = (axial_pos + HEX_OFFSETS[direction as usize])
+                .as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+                .unwrap();
+            let flow = accumulation[tile_pos];
+            *accumulation.get_mut(&downhill).unwrap() += flow;
+        }
+    }
+    accumulation
+}
+
+/// Maps each tile reachable from `sources` to the number of steps to the nearest source, using a
+/// multi-source breadth first search which only passes through tiles which are `passable`.
+///
+/// Source tiles have a distance of `0`, and are included even if they are not `passable`. Tiles
+/// which cannot be reached from any source are absent. Sources lying outside of `map_size` are
+/// ignored.
+pub fn distance_field(
+    sources: &[TilePos],
+    passable: impl Fn(TilePos) -> bool,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashMap<TilePos, u32> {
+    let mut distances: HashMap<TilePos, u32> = HashMap::default();
+    let mut queue = VecDeque::new();
+    for source in sources.iter() {
+        if source.within_map_bounds(map_size) && !distances.contains_key(source) {
+            distances.insert(*source, 0);
+            queue.push_back(*source);
+        }
+    }
+
+    while let Some(tile_pos) = queue.pop_front() {
+        let distance = distances[&tile_pos];
+        let axial_pos = AxialPos::from_tile_pos_given_coord_system(&tile_pos, hex_coord_sys);
+        for neighbor in axial_pos.neighbors() {
+            let neighbor = match neighbor.as_tile_pos_given_coord_system(map_size, hex_coord_sys) {
+                Some(neighbor) => neighbor,
+                None => continue,
+            };
+            if distances.contains_key(&neighbor) || !passable(neighbor) {
+                continue;
+            }
+            distances.insert(neighbor, distance + 1);
+            queue.push_back(neighbor);
+        }
+    }
+    distances
+}
+
+/// Returns the fronts of a multi-source breadth first search from `sources`, which only passes
+/// through tiles which are `passable`: for each step from `0` to `steps`, the tiles first reached
+/// at that step.
+///
+/// The front of step `0` holds the sources, which are included even if they are not `passable`.
+/// The returned `Vec` always has `steps + 1` fronts, the last of which may be empty if the search
+/// runs out of tiles. Sources lying outside of `map_size`, and duplicate sources, are ignored.
+pub fn expand_fronts(
+    sources: &[TilePos],
+    steps: u32,
+    passable: impl Fn(TilePos) -> bool,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> Vec<Vec<TilePos>> {
+    let mut reached: HashSet<TilePos> = HashSet::default();
+    let mut front: Vec<TilePos> = sources
+        .iter()
+        .filter(|source| source.within_map_bounds(map_size) && reached.insert(**source))
+        .copied()
+        .collect();
+
+    let mut fronts = Vec::with_capacity(steps as usize + 1);
+    for _ in 0..steps {
+        let mut next_front = Vec::new();
+        for tile_pos in front.iter() {
+            let axial_pos = AxialPos::from_tile_pos_given_coord_system(tile_pos, hex_coord_sys);
+            for neighbor in axial_pos.neighbors() {
+                let neighbor =
+                    match neighbor.as_tile_pos_given_coord_system(map_size, hex_coord_sys) {
+                        Some(neighbor) => neighbor,
+                        None => continue,
+                    };
+                if passable(neighbor) && reached.insert(neighbor) {
+                    next_front.push(neighbor);
+                }
+            }
+        }
+        fronts.push(front);
+        front = next_front;
+    }
+    fronts.push(front);
+    fronts
+}
+
+/// Returns the in-bounds neighbor of `from` with the smallest value in `field`, if that value is
+/// strictly less than the value of `from` itself. If several neighbors are equally low, the first
+/// one in [`HexDirection`] order is chosen.
+///
+/// Stepping repeatedly from a tile of a [`distance_field`] therefore leads to its nearest source.
+/// Returns `None` if `from` is a local minimum, or if it has no value in `field`. Neighbors with
+/// no value in `field` are ignored.
+pub fn descend(
+    field: &HashMap<TilePos, u32>,
+    from: TilePos,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> Option<TilePos> {
+    let mut lowest = None;
+    let mut lowest_value = *field.get(&from)?;
+    let axial_pos = AxialPos::from_tile_pos_given_coord_system(&from, hex_coord_sys);
+    for neighbor in axial_pos.neighbors() {
+        let neighbor = match neighbor.as_tile_pos_given_coord_system(map_size, hex_coord_sys) {
+            Some(neighbor) => neighbor,
+            None => continue,
+        };
+        if let Some(value) = field.get(&neighbor) {
+            if *value < lowest_value {
+                lowest = Some(neighbor);
+                lowest_value = *value;
+            }
+        }
+    }
+    lowest
+}
+
+/// Returns the edges of the map across which `value` crosses `threshold`.
+///
+/// Each edge is given as a `(tile, direction)` pair, where `tile` has a value at or above
+/// `threshold`, and its neighbor in `direction` lies within `map_size` and has a value below
+/// `threshold`. Each edge is therefore returned exactly once. Edges on the border of the map are
+/// not included.
+pub fn isocontour(
+    value: impl Fn(TilePos) -> f32,
+    threshold: f32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> Vec<(AxialPos, HexDirection)> {
+    let mut edges = Vec::new();
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            let tile_pos = TilePos { x, y };
+            if value(tile_pos) < threshold {
+                continue;
+            }
+            let axial_pos = AxialPos::from_tile_pos_given_coord_system(&tile_pos, hex_coord_sys);
+            for (direction, offset) in HEX_DIRECTIONS.iter().zip(HEX_OFFSETS.iter()) {
+                if let Some(neighbor) =
+                    (axial_pos + *offset).as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+                {
+                    if value(neighbor) < threshold {
+                        edges.push((axial_pos, *direction));
+                    }
+                }
+            }
+        }
+    }
+    edges
+}
+
+/// Returns a pseudo-random value in `[0, 1)` for the lattice point `(x, y)`, which depends only on
+/// its arguments.
+fn lattice_value(x: i64, y: i64, seed: u64) -> f32 {
+    // SplitMix64 finalizer, applied to a combination of the inputs.
+    let mut h = seed
+        ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^= h >> 31;
+    (h >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Returns smooth value noise in `[0, 1)`, sampled at the world space center of `tile`.
+///
+/// The center is first divided by the grid size, so that adjacent tiles lie roughly one unit
+/// apart, and then multiplied by `frequency`. The noise is obtained by smoothly interpolating
+/// pseudo-random values placed on the integer lattice, so lower frequencies give smoother noise.
+/// The result depends only on the arguments: the same `tile` and `seed` always give the same
+/// value.
+pub fn sample_noise(
+    tile: AxialPos,
+    seed: u64,
+    frequency: f32,
+    hex_coord_sys: HexCoordSystem,
+    grid_size: &TilemapGridSize,
+) -> f32 {
+    let center = tile.center_in_world_given_coord_system(grid_size, hex_coord_sys);
+    let point = center / Vec2::new(grid_size.x, grid_size.y) * frequency;
+
+    let (x0, y0) = (point.x.floor(), point.y.floor());
+    let smoothstep = |t: f32| t * t * (3.0 - 2.0 * t);
+    let (tx, ty) = (smoothstep(point.x - x0), smoothstep(point.y - y0));
+    let (x0, y0) = (x0 as i64, y0 as i64);
+
+    let bottom = lattice_value(x0, y0, seed) * (1.0 - tx) + lattice_value(x0 + 1, y0, seed) * tx;
+    let top =
+        lattice_value(x0, y0 + 1, seed) * (1.0 - tx) + lattice_value(x0 + 1, y0 + 1, seed) * tx;
+    bottom * (1.0 - ty) + top * ty
+}
+
+/// Returns fractal noise in `[0, 1)`, sampled at the world space center of `tile`, by summing
+/// `octaves` layers of [`sample_noise`].
+///
+/// The first octave is sampled at `base_frequency` with an amplitude of `1`, and each following
+/// octave multiplies the frequency by `lacunarity` and the amplitude by `gain`. Each octave uses a
+/// different seed derived from `seed`, and the sum is divided by the total amplitude. The result
+/// depends only on the arguments: the same `tile` and `seed` always give the same value.
+///
+/// Returns `0.0` if `octaves` is `0`.
+#[allow(clippy::too_many_arguments)]
+pub fn sample_fbm(
+    tile: AxialPos,
+    seed: u64,
+    octaves: u32,
+    lacunarity: f32,
+    gain: f32,
+    base_frequency: f32,
+    hex_coord_sys: HexCoordSystem,
+    grid_size: &TilemapGridSize,
+) -> f32 {
+    let mut total = 0.0;
+    let mut total_amplitude = 0.0;
+    let mut frequency = base_frequency;
+    let mut amplitude = 1.0;
+    for octave in 0..octaves {
+        let octave_seed = seed.wrapping_add(octave as u64);
+        total += amplitude * sample_noise(tile, octave_seed, frequency, hex_coord_sys, grid_size);
+        total_amplitude += amplitude;
+        frequency *= lacunarity;
+        amplitude *= gain;
+    }
+
+    if total_amplitude == 0.0 {
+        0.0
+    } else {
+        total / total_amplitude
+    }
+}
+
+/// Applies `iterations` passes of thermal erosion to `height`.
+///
+/// In each pass, every tile whose height exceeds that of some of its neighbors by more than
+/// `talus` moves half of the largest such excess onto those neighbors, split in proportion to how
+/// far each one exceeds `talus`. All moves within a pass are computed from the heights at the
+/// start of the pass. Material only moves between tiles lying within `map_size` which have an
+/// entry in `height`, so the total height is conserved.
+pub fn thermal_erode(
+    height: &mut HashMap<TilePos, f32>,
+    talus: f32,
+    iterations: u32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) {
+    for _ in 0..iterations {
+        let mut deltas: HashMap<TilePos, f32> = HashMap::default();
+        for (tile_pos, tile_height) in height.iter() {
+            if !tile_pos.within_map_bounds(map_size) {
+                continue;
+            }
+            let excesses: Vec<(TilePos, f32)> =
+                AxialPos::from_tile_pos_given_coord_system(tile_pos, hex_coord_sys)
+                    .neighbors()
+                    .iter()
+                    .filter_map(|neighbor| {
+                        neighbor.as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+                    })
+                    .filter_map(|neighbor| {
+                        let excess = tile_height - height.get(&neighbor)? - talus;
+                        if excess > 0.0 {
+                            Some((neighbor, excess))
+                        } else {
+                            None
+                        }
+                    })
+                    .collect();
+            if excesses.is_empty() {
+                continue;
+            }
+
+            let max_excess = excesses
+                .iter()
+                .map(|(_, excess)| *excess)
+                .fold(0.0, f32::max);
+            let total_excess: f32 = excesses.iter().map(|(_, excess)| *excess).sum();
+            let moved = max_excess / 2.0;
+            *deltas.entry(*tile_pos).or_insert(0.0) -= moved;
+            for (neighbor, excess) in excesses {
+                *deltas.entry(neighbor).or_insert(0.0) += moved * excess / total_excess;
+            }
+        }
+
+        if deltas.is_empty() {
+            break;
+        }
+        for (tile_pos, delta) in deltas {
+            *height.get_mut(&tile_pos).unwrap() += delta;
+        }
+    }
+}
+
+/// Maps each tile of the map to its slope: the largest absolute difference in `height` between it
+/// and any of its in-bounds neighbors, divided by the world space distance between their centers.
+///
+/// Tiles with no in-bounds neighbors have a slope of `0.0`.
+pub fn slope(
+    height: impl Fn(TilePos) -> f32,
+    grid_size: &TilemapGridSize,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashMap<TilePos, f32> {
+    let mut slopes = HashMap::default();
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            let tile_pos = TilePos { x, y };
+            let axial_pos = AxialPos::from_tile_pos_given_coord_system(&tile_pos, hex_coord_sys);
+            let tile_height = height(tile_pos);
+            let center = axial_pos.center_in_world_given_coord_system(grid_size, hex_coord_sys);
+            let max_slope = axial_pos
+                .neighbors()
+                .iter()
+                .filter_map(|neighbor| {
+                    let neighbor_pos =
+                        neighbor.as_tile_pos_given_coord_system(map_size, hex_coord_sys)?;
+                    let distance = center.distance(
+                        neighbor.center_in_world_given_coord_system(grid_size, hex_coord_sys),
+                    );
+                    Some((height(neighbor_pos) - tile_height).abs() / distance)
+                })
+                .fold(0.0, f32::max);
+            slopes.insert(tile_pos, max_slope);
+        }
+    }
+    slopes
+}
+
+/// Maps each tile of the map to its aspect: the direction of its lowest in-bounds neighbor, i.e.
+/// the direction of steepest descent. If several neighbors are equally low, the first one in
+/// [`HexDirection`] order is chosen.
+///
+/// Tiles which have no strictly lower neighbor, such as flat tiles and sinks, are omitted. This is
+/// the same as [`flow_directions`], without the `None` entries.
+pub fn aspect(
+    height: impl Fn(TilePos) -> f32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashMap<TilePos, HexDirection> {
+    let mut aspects = HashMap::default();
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            let tile_pos = TilePos { x, y };
+            if let Some(direction) = downhill_direction(tile_pos, &height, hex_coord_sys, map_size)
+            {
+                aspects.insert(tile_pos, direction);
+            }
+        }
+    }
+    aspects
+}
+
+/// Maps each tile of the map to its ambient occlusion, from `0.0` to `1.0`: the fraction of its
+/// six neighbors which are strictly taller than the tile itself.
+///
+/// Neighbors lying outside of `map_size` never occlude, so a pit surrounded by taller tiles has an
+/// occlusion of `1.0`, while a peak, or a flat tile, has an occlusion of `0.0`.
+pub fn ambient_occlusion(
+    height: impl Fn(TilePos) -> f32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashMap<TilePos, f32> {
+    let mut occlusion = HashMap::default();
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            let tile_pos = TilePos { x, y };
+            let tile_height = height(tile_pos);
+            let taller = AxialPos::from_tile_pos_given_coord_system(&tile_pos, hex_coord_sys)
+                .neighbors()
+                .iter()
+                .filter_map(|neighbor| {
+                    neighbor.as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+                })
+                .filter(|neighbor| height(*neighbor) > tile_height)
+                .count();
+            occlusion.insert(tile_pos, taller as f32 / HEX_OFFSETS.len() as f32);
+        }
+    }
+    occlusion
+}
+
+/// Returns the tiles of the map which lie in shadow, when lit by light travelling in `light_dir`.
+///
+/// The map is marched along straight lines in `light_dir`, starting from the tiles on its upwind
+/// edge. Along each line, a shadow line is carried which starts at the height of each tile, and
+/// drops by `sun_slope` per tile travelled. Tiles lower than the shadow line cast by the tiles
+/// before them are in shadow, so a tall tile casts a shadow which shortens as `sun_slope` grows.
+pub fn cast_shadows(
+    height: impl Fn(TilePos) -> f32,
+    light_dir: HexDirection,
+    sun_slope: f32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashSet<TilePos> {
+    let offset = HEX_OFFSETS[light_dir as usize];
+    let mut shadowed = HashSet::default();
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            let start =
+                AxialPos::from_tile_pos_given_coord_system(&TilePos { x, y }, hex_coord_sys);
+            if (start - offset)
+                .as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+                .is_some()
+            {
+                continue;
+            }
+
+            let mut shadow = f32::NEG_INFINITY;
+            let mut current = start;
+            while let Some(tile_pos) =
+                current.as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+            {
+                let tile_height = height(tile_pos);
+                if tile_height < shadow {
+                    shadowed.insert(tile_pos);
+                }
+                shadow = shadow.max(tile_height) - sun_slope;
+                current = current + offset;
+            }
+        }
+    }
+    shadowed
+}
+
+/// Maps each tile of the map within `max_distance` of `source` to the influence of `source` on it:
+/// `strength * falloff^d`, where `d` is the hex distance from `source`.
+///
+/// With a `falloff` between `0.0` and `1.0`, influence decreases with distance. Tiles further than
+/// `max_distance` have no influence, and are omitted. The influences of several sources can be
+/// combined by summing their maps.
+pub fn influence(
+    source: AxialPos,
+    strength: f32,
+    falloff: f32,
+    max_distance: u32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashMap<TilePos, f32> {
+    source
+        .range(max_distance)
+        .into_iter()
+        .filter_map(|tile| {
+            let tile_pos = tile.as_tile_pos_given_coord_system(map_size, hex_coord_sys)?;
+            let distance = tile.distance_from(&source);
+            Some((tile_pos, strength * falloff.powi(distance)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP_SIZE: TilemapSize = TilemapSize { x: 6, y: 5 };
+
+    #[test]
+    fn flow_accumulation_collects_at_the_bottom_of_a_uniform_slope() {
+        for hex_coord_sys in [HexCoordSystem::Row, HexCoordSystem::RowEven] {
+            let accumulation =
+                flow_accumulation(|tile_pos| tile_pos.y as f32, hex_coord_sys, &MAP_SIZE);
+
+            let bottom_total: u32 = (0..MAP_SIZE.x)
+                .map(|x| accumulation[&TilePos { x, y: 0 }])
+                .sum();
+            assert_eq!(bottom_total as usize, MAP_SIZE.count());
+
+            let bottom_max = (0..MAP_SIZE.x)
+                .map(|x| accumulation[&TilePos { x, y: 0 }])
+                .max()
+                .unwrap();
+            for (tile_pos, flow) in accumulation.iter() {
+                if tile_pos.y > 0 {
+                    assert!(*flow < bottom_max);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn idw_sample_is_exact_at_samples_and_blends_between_them() {
+        let samples = [
+            (AxialPos { q: 0, r: 0 }, 0.0),
+            (AxialPos { q: 4, r: 0 }, 8.0),
+            (AxialPos { q: 0, r: 4 }, 2.0),
+        ];
+        for (tile, value) in samples.iter() {
+            assert_eq!(
+                idw_sample(FractionalAxialPos::from(*tile), &samples, 2.0),
+                *value
+            );
+        }
+
+        let midpoint = FractionalAxialPos { q: 2.0, r: 0.0 };
+        let between = idw_sample(midpoint, &samples[..2], 2.0);
+        assert!((between - 4.0).abs() < 1e-5);
+
+        let blended = idw_sample(FractionalAxialPos { q: 1.0, r: 1.0 }, &samples, 2.0);
+        assert!(0.0 < blended && blended < 8.0);
+        assert_eq!(idw_sample(midpoint, &[], 2.0), 0.0);
+    }
+
+    #[test]
+    fn flow_directions_on_a_uniform_slope_and_a_pit() {
+        // Rises towards the east in world space.
+        let slope = |tile_pos: TilePos| tile_pos.x as f32 + tile_pos.y as f32 / 2.0;
+        let directions = flow_directions(slope, HexCoordSystem::Row, &MAP_SIZE);
+        for (tile_pos, direction) in directions.iter() {
+            let expected = match (tile_pos.x, tile_pos.y) {
+                (0, 0) => None,
+                (0, _) => Some(HexDirection::Four),
+                _ => Some(HexDirection::Three),
+            };
+            assert_eq!(*direction, expected);
+        }
+
+        let pit = AxialPos { q: 2, r: 2 };
+        let bowl = |tile_pos: TilePos| AxialPos::from(&tile_pos).distance_from(&pit) as f32;
+        let directions = flow_directions(bowl, HexCoordSystem::Row, &MAP_SIZE);
+        assert_eq!(directions[&TilePos { x: 2, y: 2 }], None);
+        assert_eq!(
+            directions
+                .values()
+                .filter(|direction| direction.is_none())
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn distance_field_counts_rings_from_the_nearest_source() {
+        let source = TilePos { x: 3, y: 2 };
+        let field = distance_field(&[source], |_| true, HexCoordSystem::Row, &MAP_SIZE);
+        let source_axial = AxialPos::from(&source);
+        for radius in 0..4 {
+            for axial_pos in source_axial.ring(radius) {
+                if let Some(tile_pos) = axial_pos.as_tile_pos(&MAP_SIZE) {
+                    assert_eq!(field[&tile_pos], radius);
+                }
+            }
+        }
+
+        let sources = [TilePos { x: 0, y: 0 }, TilePos { x: 5, y: 4 }];
+        let field = distance_field(&sources, |_| true, HexCoordSystem::Row, &MAP_SIZE);
+        assert_eq!(field.len(), MAP_SIZE.count());
+        for (tile_pos, distance) in field.iter() {
+            let nearest = sources
+                .iter()
+                .map(|source| AxialPos::from(source).distance_from(&AxialPos::from(tile_pos)))
+                .min()
+                .unwrap();
+            assert_eq!(*distance, nearest as u32);
+        }
+    }
+
+    #[test]
+    fn descend_moves_toward_the_source() {
+        let source = TilePos { x: 1, y: 1 };
+        // A wall forces the descent to detour around it.
+        let passable = |tile_pos: TilePos| tile_pos.x != 3 || tile_pos.y == 4;
+        let field = distance_field(&[source], passable, HexCoordSystem::RowEven, &MAP_SIZE);
+
+        let mut current = TilePos { x: 5, y: 1 };
+        let mut steps = 0;
+        while let Some(next) = descend(&field, current, HexCoordSystem::RowEven, &MAP_SIZE) {
+            assert_eq!(field[&next] + 1, field[&current]);
+            current = next;
+            steps += 1;
+        }
+        assert_eq!(current, source);
+        assert_eq!(steps, field[&TilePos { x: 5, y: 1 }]);
+        assert_eq!(
+            descend(
+                &field,
+                TilePos { x: 3, y: 0 },
+                HexCoordSystem::RowEven,
+                &MAP_SIZE
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn isocontour_of_a_gradient() {
+        let gradient = |tile_pos: TilePos| tile_pos.x as f32;
+        let mut edges = isocontour(gradient, 2.5, HexCoordSystem::Row, &MAP_SIZE);
+        edges.sort();
+
+        let mut expected = Vec::new();
+        for r in 0..MAP_SIZE.y as i32 {
+            let axial_pos = AxialPos { q: 3, r };
+            if r + 1 < MAP_SIZE.y as i32 {
+                expected.push((axial_pos, HexDirection::Two));
+            }
+            expected.push((axial_pos, HexDirection::Three));
+        }
+        expected.sort();
+        assert_eq!(edges, expected);
+    }
+
+    const GRID_SIZE: TilemapGridSize = TilemapGridSize { x: 16.0, y: 16.0 };
+
+    #[test]
+    fn sample_noise_is_deterministic_and_smooth() {
+        let noise = |tile: AxialPos, seed: u64| {
+            sample_noise(tile, seed, 0.1, HexCoordSystem::Row, &GRID_SIZE)
+        };
+        let tiles = AxialPos { q: 0, r: 0 }.range(12);
+
+        let mut differs = false;
+        for tile in tiles.iter() {
+            let value = noise(*tile, 42);
+            assert_eq!(value, noise(*tile, 42));
+            assert!((0.0..1.0).contains(&value));
+            differs |= value != noise(*tile, 43);
+
+            // At a low frequency, neighboring tiles have similar values.
+            for neighbor in tile.neighbors() {
+                assert!((value - noise(neighbor, 42)).abs() < 0.3);
+            }
+        }
+        assert!(differs);
+    }
+
+    #[test]
+    fn sample_fbm_layers_octaves_within_range() {
+        let noise = |tile: AxialPos, seed: u64, frequency: f32| {
+            sample_noise(tile, seed, frequency, HexCoordSystem::Row, &GRID_SIZE)
+        };
+        let fbm = |tile: AxialPos, octaves: u32| {
+            sample_fbm(
+                tile,
+                7,
+                octaves,
+                2.0,
+                0.5,
+                0.05,
+                HexCoordSystem::Row,
+                &GRID_SIZE,
+            )
+        };
+
+        for tile in (AxialPos { q: 0, r: 0 }).range(12) {
+            assert_eq!(fbm(tile, 0), 0.0);
+            assert_eq!(fbm(tile, 1), noise(tile, 7, 0.05));
+
+            // The second octave adds finer detail, at twice the frequency and half the amplitude.
+            let layered = (noise(tile, 7, 0.05) + 0.5 * noise(tile, 8, 0.1)) / 1.5;
+            assert!((fbm(tile, 2) - layered).abs() < 1e-6);
+
+            assert!((0.0..1.0).contains(&fbm(tile, 6)));
+        }
+    }
+
+    #[test]
+    fn thermal_erode_spreads_a_spike_and_conserves_height() {
+        let spike = TilePos { x: 3, y: 2 };
+        let mut height: HashMap<TilePos, f32> = HashMap::default();
+        for y in 0..MAP_SIZE.y {
+            for x in 0..MAP_SIZE.x {
+                height.insert(TilePos { x, y }, 0.0);
+            }
+        }
+        height.insert(spike, 10.0);
+
+        thermal_erode(&mut height, 1.0, 20, HexCoordSystem::Row, &MAP_SIZE);
+
+        let total: f32 = height.values().sum();
+        assert!((total - 10.0).abs() < 1e-4);
+        assert!(height[&spike] < 10.0);
+        for neighbor in AxialPos::from(&spike).neighbors() {
+            assert!(height[&neighbor.as_tile_pos(&MAP_SIZE).unwrap()] > 0.0);
+        }
+    }
+
+    #[test]
+    fn slope_of_a_uniform_plane() {
+        // Rises by half a world unit per world unit towards the east.
+        let plane =
+            |tile_pos: TilePos| 0.5 * AxialPos::from(&tile_pos).center_in_world_row(&GRID_SIZE).x;
+        let slopes = slope(plane, &GRID_SIZE, HexCoordSystem::Row, &MAP_SIZE);
+        assert_eq!(slopes.len(), MAP_SIZE.count());
+        for value in slopes.values() {
+            assert!((value - 0.5).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn aspect_of_a_planar_slope() {
+        // Rises towards the north in world space, so the steepest descent is southward.
+        let plane = |tile_pos: TilePos| AxialPos::from(&tile_pos).center_in_world_col(&GRID_SIZE).y;
+        let aspects = aspect(plane, HexCoordSystem::Column, &MAP_SIZE);
+        for y in 1..MAP_SIZE.y - 1 {
+            for x in 1..MAP_SIZE.x - 1 {
+                assert_eq!(aspects[&TilePos { x, y }], HexDirection::Four);
+            }
+        }
+        assert!(!aspects.contains_key(&TilePos { x: 0, y: 0 }));
+
+        let flat = aspect(|_| 1.0, HexCoordSystem::Column, &MAP_SIZE);
+        assert!(flat.is_empty());
+    }
+
+    #[test]
+    fn expand_fronts_start_at_the_sources_and_grow() {
+        let source = TilePos { x: 2, y: 2 };
+        let fronts = expand_fronts(
+            &[source, source],
+            2,
+            |_| true,
+            HexCoordSystem::Row,
+            &MAP_SIZE,
+        );
+        assert_eq!(fronts.len(), 3);
+        assert_eq!(fronts[0], vec![source]);
+        for (step, front) in fronts.iter().enumerate() {
+            let expected: HashSet<TilePos> = AxialPos::from(&source)
+                .ring(step as u32)
+                .into_iter()
+                .filter_map(|axial_pos| axial_pos.as_tile_pos(&MAP_SIZE))
+                .collect();
+            assert_eq!(
+                front.iter().copied().collect::<HashSet<TilePos>>(),
+                expected
+            );
+        }
+        assert!(fronts.windows(2).all(|pair| pair[0].len() < pair[1].len()));
+
+        // A walled in source runs out of tiles.
+        let fronts = expand_fronts(&[source], 2, |_| false, HexCoordSystem::Row, &MAP_SIZE);
+        assert_eq!(fronts, vec![vec![source], vec![], vec![]]);
+    }
+
+    #[test]
+    fn ambient_occlusion_of_a_pit_and_a_peak() {
+        let center = TilePos { x: 2, y: 2 };
+        let bump = |center_height: f32| {
+            move |tile_pos: TilePos| {
+                if tile_pos == center {
+                    center_height
+                } else {
+                    1.0
+                }
+            }
+        };
+
+        let pit = ambient_occlusion(bump(0.0), HexCoordSystem::Row, &MAP_SIZE);
+        assert_eq!(pit.len(), MAP_SIZE.count());
+        assert_eq!(pit[&center], 1.0);
+        assert!(pit
+            .iter()
+            .all(|(tile_pos, occlusion)| *tile_pos == center || *occlusion == 0.0));
+
+        let peak = ambient_occlusion(bump(2.0), HexCoordSystem::Row, &MAP_SIZE);
+        assert_eq!(peak[&center], 0.0);
+        for neighbor in AxialPos::from(&center).neighbors() {
+            assert_eq!(peak[&neighbor.as_tile_pos(&MAP_SIZE).unwrap()], 1.0 / 6.0);
+        }
+    }
+
+    #[test]
+    fn cast_shadows_fall_downwind() {
+        let tower = TilePos { x: 1, y: 2 };
+        let height = |tile_pos: TilePos| if tile_pos == tower { 3.0 } else { 0.0 };
+
+        let shadowed = cast_shadows(
+            height,
+            HexDirection::Zero,
+            1.0,
+            HexCoordSystem::Row,
+            &MAP_SIZE,
+        );
+        let expected: HashSet<TilePos> = [TilePos { x: 2, y: 2 }, TilePos { x: 3, y: 2 }]
+            .into_iter()
+            .collect();
+        assert_eq!(shadowed, expected);
+
+        let shadowed = cast_shadows(
+            height,
+            HexDirection::Three,
+            1.0,
+            HexCoordSystem::Row,
+            &MAP_SIZE,
+        );
+        assert_eq!(shadowed, [TilePos { x: 0, y: 2 }].into_iter().collect());
+
+        // A high sun casts no shadow.
+        assert!(cast_shadows(
+            height,
+            HexDirection::Zero,
+            3.0,
+            HexCoordSystem::Row,
+            &MAP_SIZE
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn influence_falls_off_with_distance() {
+        let source = AxialPos { q: 2, r: 2 };
+        let values = influence(source, 8.0, 0.5, 3, HexCoordSystem::Row, &MAP_SIZE);
+        assert_eq!(values[&TilePos { x: 2, y: 2 }], 8.0);
+        for (tile_pos, value) in values.iter() {
+            let distance = AxialPos::from(tile_pos).distance_from(&source);
+            assert!(distance <= 3);
+            assert_eq!(*value, 8.0 * 0.5f32.powi(distance));
+        }
+        // Moving away from the source along a row, influence strictly decreases.
+        let row: Vec<f32> = (2..MAP_SIZE.x)
+            .map(|x| values[&TilePos { x, y: 2 }])
+            .collect();
+        assert!(row.windows(2).all(|pair| pair[0] > pair[1]));
+
+        let source_only = influence(source, 8.0, 0.5, 0, HexCoordSystem::Row, &MAP_SIZE);
+        assert_eq!(source_only.len(), 1);
+        assert_eq!(source_only[&TilePos { x: 2, y: 2 }], 8.0);
+    }
+}
@@ -0,0 +1,290 @@
+use crate::helpers::hex_grid::axial::AxialPos;
+use crate::helpers::hex_grid::edge::EdgeId;
+use crate::map::HexCoordSystem;
+use crate::tiles::TilePos;
+use crate::TilemapSize;
+use bevy::utils::HashSet;
+use rand::Rng;
+
+/// Returns the edges of a minimum spanning tree over `points`, where edges are weighted by the hex
+/// distance between their endpoints.
+///
+/// Edges are given as pairs of indices into `points`, in the order they were added by Prim's
+/// algorithm (starting from the first point). The first index of each pair is the point already
+/// in the tree. Returns no edges if there are fewer than two points.
+pub fn mst(points: &[AxialPos]) -> Vec<(usize, usize)> {
+    let n = points.len();
+    let mut edges = Vec::with_capacity(n.saturating_sub(1));
+    if n < 2 {
+        return edges;
+    }
+
+    let mut in_tree = vec![false; n];
+    // For each point not in the tree: the distance to, and the index of, the nearest tree point.
+    let mut nearest: Vec<(i32, usize)> = points
+        .iter()
+        .map(|point| (point.distance_from(&points[0]), 0))
+        .collect();
+    in_tree[0] = true;
+
+    for _ in 1..n {
+        let next = (0..n)
+            .filter(|&i| !in_tree[i])
+            .min_by_key(|&i| nearest[i].0)
+            .unwrap();
+        in_tree[next] = true;
+        edges.push((nearest[next].1, next));
+
+        for i in 0..n {
+            if !in_tree[i] {
+                let distance = points[i].distance_from(&points[next]);
+                if distance < nearest[i].0 {
+                    nearest[i] = (distance, next);
+                }
+            }
+        }
+    }
+
+    edges
+}
+
+/// Returns `start` followed by `waypoints` in the order visited by the nearest neighbor heuristic:
+/// from the current tile, the nearest unvisited waypoint by hex distance is visited next. Ties are
+/// broken in favor of the earliest waypoint.
+///
+/// The resulting tour is not necessarily the shortest one, but is usually reasonable for small
+/// sets of waypoints. Each waypoint is visited exactly once, even if it coincides with `start` or
+/// with another waypoint.
+pub fn nearest_neighbor_tour(
+    start: TilePos,
+    waypoints: &[TilePos],
+    hex_coord_sys: HexCoordSystem,
+) -> Vec<TilePos> {
+    let mut tour = Vec::with_capacity(waypoints.len() + 1);
+    tour.push(start);
+
+    let axial_waypoints: Vec<AxialPos> = waypoints
+        .iter()
+        .map(|waypoint| AxialPos::from_tile_pos_given_coord_system(waypoint, hex_coord_sys))
+        .collect();
+    let mut visited = vec![false; waypoints.len()];
+    let mut current = AxialPos::from_tile_pos_given_coord_system(&start, hex_coord_sys);
+    for _ in 0..waypoints.len() {
+        let next = (0..waypoints.len())
+            .filter(|&i| !visited[i])
+            .min_by_key(|&i| axial_waypoints[i].distance_from(&current))
+            .unwrap();
+        visited[next] = true;
+        tour.push(waypoints[next]);
+        current = axial_waypoints[next];
+    }
+
+    tour
+}
+
+/// Generates a perfect maze over the tiles of the map, using a randomized depth first search.
+///
+/// Returns the set of open edges (passages) between adjacent tiles. Open edges form a spanning
+/// tree over all tiles of the map, so there is exactly one path between any two tiles, and there
+/// are `map_size.count() - 1` passages. Every other edge between adjacent tiles is a wall.
+pub fn generate_maze(
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+    rng: &mut impl Rng,
+) -> HashSet<EdgeId> {
+    let mut passages = HashSet::default();
+    if map_size.count() == 0 {
+        return passages;
+    }
+
+    let start = AxialPos::from_tile_pos_given_coord_system(&TilePos { x: 0, y: 0 }, hex_coord_sys);
+    let mut visited = HashSet::default();
+    visited.insert(start);
+    let mut stack = vec![start];
+
+    while let Some(&current) = stack.last() {
+        let unvisited: Vec<AxialPos> = current
+            .neighbors()
+            .into_iter()
+            .filter(|neighbor| {
+                neighbor
+                    .as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+                    .is_some()
+                    && !visited.contains(neighbor)
+            })
+            .collect();
+
+        if unvisited.is_empty() {
+            stack.pop();
+        } else {
+            let next = unvisited[rng.gen_range(0..unvisited.len())];
+            passages.insert(EdgeId::new(current, next));
+            visited.insert(next);
+            stack.push(next);
+        }
+    }
+
+    passages
+}
+
+/// Generates a simple dungeon layout of up to `room_count` hexagonal rooms of radius
+/// `room_radius`, connected by corridors.
+///
+/// Room centers are placed at random, such that each room lies entirely within `map_size` and
+/// does not overlap any other room. If a room cannot be placed after a bounded number of
+/// attempts, fewer rooms are returned. Corridors follow the edges of the [`mst`] of the room
+/// centers, and are given as [`AxialPos::line_to`] from one center to the other, so that every
+/// room can be reached from every other one.
+///
+/// Returns the tiles of each room, and the tiles of each corridor.
+pub fn rooms_and_corridors(
+    room_count: u32,
+    room_radius: u32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+    rng: &mut impl Rng,
+) -> (Vec<HashSet<AxialPos>>, Vec<Vec<AxialPos>>) {
+    if map_size.count() == 0 {
+        return (Vec::new(), Vec::new());
+    }
+
+    let radius = room_radius as i32;
+    let mut centers: Vec<AxialPos> = Vec::with_capacity(room_count as usize);
+    let mut attempts = 100 * room_count;
+    while centers.len() < room_count as usize && attempts > 0 {
+        attempts -= 1;
+        let center_tile_pos = TilePos {
+            x: rng.gen_range(0..map_size.x),
+            y: rng.gen_range(0..map_size.y),
+        };
+        let center = AxialPos::from_tile_pos_given_coord_system(&center_tile_pos, hex_coord_sys);
+        if center.range(room_radius).iter().all(|axial_pos| {
+            axial_pos
+                .as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+                .is_some()
+        }) && centers
+            .iter()
+            .all(|other| other.distance_from(&center) > 2 * radius)
+        {
+            centers.push(center);
+        }
+    }
+
+    let rooms = centers
+        .iter()
+        .map(|center| center.range(room_radius).into_iter().collect())
+        .collect();
+    let corridors = mst(&centers)
+        .into_iter()
+        .map(|(a, b)| centers[a].line_to(&centers[b]))
+        .collect();
+
+    (rooms, corridors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::hex_grid::region::is_connected;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn mst_of_four_points() {
+        let points = [
+            AxialPos { q: 0, r: 0 },
+            AxialPos { q: 5, r: 0 },
+            AxialPos { q: 1, r: 0 },
+            AxialPos { q: 5, r: 1 },
+        ];
+        let edges = mst(&points);
+        assert_eq!(edges, vec![(0, 2), (2, 1), (1, 3)]);
+
+        let total: i32 = edges
+            .iter()
+            .map(|&(a, b)| points[a].distance_from(&points[b]))
+            .sum();
+        assert_eq!(total, 6);
+        assert!(mst(&points[..1]).is_empty());
+    }
+
+    #[test]
+    fn generate_maze_opens_a_spanning_tree() {
+        let map_size = TilemapSize { x: 7, y: 5 };
+        for hex_coord_sys in [HexCoordSystem::Row, HexCoordSystem::ColumnOdd] {
+            let passages = generate_maze(hex_coord_sys, &map_size, &mut StdRng::seed_from_u64(3));
+            assert_eq!(passages.len(), map_size.count() - 1);
+
+            // Walking the passages from any tile reaches every tile of the map.
+            let start =
+                AxialPos::from_tile_pos_given_coord_system(&TilePos { x: 3, y: 2 }, hex_coord_sys);
+            let mut reached = HashSet::default();
+            reached.insert(start);
+            let mut stack = vec![start];
+            while let Some(current) = stack.pop() {
+                for neighbor in current.neighbors() {
+                    if passages.contains(&EdgeId::new(current, neighbor))
+                        && reached.insert(neighbor)
+                    {
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            assert_eq!(reached.len(), map_size.count());
+        }
+    }
+
+    #[test]
+    fn nearest_neighbor_tour_visits_each_waypoint_once() {
+        let start = TilePos { x: 0, y: 0 };
+        let waypoints = [
+            TilePos { x: 6, y: 0 },
+            TilePos { x: 1, y: 0 },
+            TilePos { x: 0, y: 0 },
+            TilePos { x: 3, y: 0 },
+            TilePos { x: 3, y: 0 },
+        ];
+        let tour = nearest_neighbor_tour(start, &waypoints, HexCoordSystem::Row);
+        assert_eq!(
+            tour,
+            vec![
+                start,
+                TilePos { x: 0, y: 0 },
+                TilePos { x: 1, y: 0 },
+                TilePos { x: 3, y: 0 },
+                TilePos { x: 3, y: 0 },
+                TilePos { x: 6, y: 0 },
+            ]
+        );
+
+        let mut visited = tour[1..].to_vec();
+        let mut expected = waypoints.to_vec();
+        visited.sort_by_key(|tile_pos| (tile_pos.y, tile_pos.x));
+        expected.sort_by_key(|tile_pos| (tile_pos.y, tile_pos.x));
+        assert_eq!(visited, expected);
+    }
+
+    #[test]
+    fn rooms_and_corridors_are_fully_connected() {
+        let map_size = TilemapSize { x: 20, y: 20 };
+        for seed in 0..4 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let (rooms, corridors) =
+                rooms_and_corridors(5, 1, HexCoordSystem::Row, &map_size, &mut rng);
+            assert!(rooms.len() > 1);
+            assert_eq!(corridors.len(), rooms.len() - 1);
+
+            let mut tiles: HashSet<AxialPos> = HashSet::default();
+            for room in rooms.iter() {
+                assert!(room
+                    .iter()
+                    .all(|tile| tile.as_tile_pos(&map_size).is_some()));
+                tiles.extend(room.iter().copied());
+            }
+            for corridor in corridors.iter() {
+                tiles.extend(corridor.iter().copied());
+            }
+            assert!(is_connected(&tiles));
+        }
+    }
+}
@@ -0,0 +1,514 @@
+use crate::helpers::hex_grid::axial::{AxialPos, FractionalAxialPos};
+use crate::helpers::hex_grid::edge::VertexId;
+use crate::helpers::hex_grid::neighbors::{HexDirection, HEX_OFFSETS};
+use crate::map::HexCoordSystem;
+use crate::tiles::TilePos;
+use crate::{TilemapGridSize, TilemapSize, TilemapType};
+use bevy::math::Vec2;
+use bevy::utils::HashSet;
+use std::cmp::Ordering;
+use std::f32::consts::TAU;
+
+impl AxialPos {
+    /// Returns the hexes lying on the straight line from `self` to `other`, in order, including
+    /// both endpoints.
+    ///
+    /// Sample points are nudged by a tiny amount so that points lying exactly on the edge between
+    /// two hexes are rounded consistently. See the Red Blob Games article on
+    /// [line drawing](https://www.redblobgames.com/grids/hexagons/#line-drawing) for more
+    /// information.
+    pub fn line_to(&self, other: &AxialPos) -> Vec<AxialPos> {
+        let n = self.distance_from(other);
+        if n == 0 {
+            return vec![*self];
+        }
+
+        let start = FractionalAxialPos {
+            q: self.q as f32 + 1e-6,
+            r: self.r as f32 + 1e-6,
+        };
+        let end = FractionalAxialPos {
+            q: other.q as f32 + 1e-6,
+            r: other.r as f32 + 1e-6,
+        };
+
+        (0..=n)
+            .map(|i| {
+                let t = i as f32 / n as f32;
+                FractionalAxialPos {
+                    q: start.q + (end.q - start.q) * t,
+                    r: start.r + (end.r - start.r) * t,
+                }
+                .round()
+            })
+            .collect()
+    }
+}
+
+/// Returns the average `cover` value of the tiles lying strictly between `from` and `to` on the
+/// hex line joining them. Endpoints are excluded, as are intermediate tiles that lie outside of
+/// `map_size`.
+///
+/// `cover` should return a value between `0.0` (no cover) and `1.0` (full cover).
+///
+/// Returns `0.0` if there are no intermediate tiles.
+pub fn cover_fraction(
+    from: TilePos,
+    to: TilePos,
+    cover: impl Fn(TilePos) -> f32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> f32 {
+    let line = AxialPos::from_tile_pos_given_coord_system(&from, hex_coord_sys).line_to(
+        &AxialPos::from_tile_pos_given_coord_system(&to, hex_coord_sys),
+    );
+    let intermediate = if line.len() > 2 {
+        &line[1..(line.len() - 1)]
+    } else {
+        &[]
+    };
+
+    let (total, count) = intermediate
+        .iter()
+        .filter_map(|axial_pos| axial_pos.as_tile_pos_given_coord_system(map_size, hex_coord_sys))
+        .fold((0.0, 0), |(total, count), tile_pos| {
+            (total + cover(tile_pos), count + 1)
+        });
+
+    if count == 0 {
+        0.0
+    } else {
+        total / count as f32
+    }
+}
+
+/// Returns the hexes covered by a line from `a` to `b` which is `width` hexes thick on either side.
+///
+/// This is the union of the ranges of radius `width` around each hex of the thin line
+/// [`a.line_to(b)`](AxialPos::line_to). If `width` is `0`, this is the thin line itself.
+pub fn thick_line(a: AxialPos, b: AxialPos, width: u32) -> HashSet<AxialPos> {
+    a.line_to(&b)
+        .iter()
+        .flat_map(|axial_pos| axial_pos.range(width))
+        .collect()
+}
+
+/// Returns the line from `a` to `b` (see [`AxialPos::line_to`]), shifted sideways by `offset`
+/// steps.
+///
+/// The line's direction is the sector of `b` relative to `a` (see [`AxialPos::sector_from`]). A
+/// positive `offset` shifts the line towards the [`HexDirection`]
+/// lying one step counter-clockwise of that direction, while a negative `offset` shifts it the
+/// opposite way. An `offset` of `0` returns the original line, and an `offset` of `1` returns a
+/// line whose every tile is adjacent to the corresponding tile of the original.
+pub fn parallel_line(a: AxialPos, b: AxialPos, offset: i32) -> Vec<AxialPos> {
+    let side = b.sector_from(&a) + 1;
+    let shift = offset * HEX_OFFSETS[side as usize];
+    a.line_to(&b)
+        .into_iter()
+        .map(|axial_pos| axial_pos + shift)
+        .collect()
+}
+
+/// Returns `true` if the segment from `start` to `end` intersects (or touches) the convex polygon
+/// with the given `corners`, using the separating axis theorem.
+fn segment_touches_polygon(start: Vec2, end: Vec2, corners: &[Vec2], epsilon: f32) -> bool {
+    let segment = end - start;
+    let edge_normals = corners
+        .iter()
+        .zip(corners.iter().cycle().skip(1))
+        .map(|(a, b)| (*b - *a).perp());
+
+    for axis in edge_normals.chain(std::iter::once(segment.perp())) {
+        if axis == Vec2::ZERO {
+            continue;
+        }
+        let axis = axis.normalize();
+        let (segment_min, segment_max) = {
+            let (a, b) = (start.dot(axis), end.dot(axis));
+            (a.min(b), a.max(b))
+        };
+        let (polygon_min, polygon_max) = corners
+            .iter()
+            .map(|corner| corner.dot(axis))
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), x| {
+                (min.min(x), max.max(x))
+            });
+        if segment_max < polygon_min - epsilon || polygon_max < segment_min - epsilon {
+            return false;
+        }
+    }
+    true
+}
+
+/// Returns every hex touched by the world space segment joining the centers of `a` and `b`,
+/// ordered from `a` to `b`.
+///
+/// Unlike [`AxialPos::line_to`], which picks a single hex per step, this "supercover" includes
+/// all hexes the segment passes through, including both hexes on either side of a corner that
+/// the segment merely grazes.
+pub fn supercover_line(
+    a: AxialPos,
+    b: AxialPos,
+    grid_size: &TilemapGridSize,
+    hex_coord_sys: HexCoordSystem,
+) -> Vec<AxialPos> {
+    let start = a.center_in_world_given_coord_system(grid_size, hex_coord_sys);
+    let end = b.center_in_world_given_coord_system(grid_size, hex_coord_sys);
+    let direction = end - start;
+    let epsilon = 1e-4 * grid_size.x.max(grid_size.y);
+
+    // Every hex touched by the segment lies within one step of the standard hex line.
+    let mut tiles: Vec<(AxialPos, f32)> = thick_line(a, b, 1)
+        .into_iter()
+        .filter(|tile| {
+            let corners = tile.corners_in_world(grid_size, hex_coord_sys);
+            segment_touches_polygon(start, end, &corners, epsilon)
+        })
+        .map(|tile| {
+            let center = tile.center_in_world_given_coord_system(grid_size, hex_coord_sys);
+            (tile, (center - start).dot(direction))
+        })
+        .collect();
+    tiles.sort_by(|(tile_a, t_a), (tile_b, t_b)| {
+        t_a.partial_cmp(t_b)
+            .unwrap_or(Ordering::Equal)
+            .then(tile_a.cmp(tile_b))
+    });
+
+    tiles.into_iter().map(|(tile, _)| tile).collect()
+}
+
+/// Returns the world position of the hex corner nearest to `world_pos`, along with a
+/// [`VertexId`] identifying it. The identifier is the same no matter which of the three hexes
+/// sharing the corner it is obtained from.
+///
+/// Corners are those given by [`AxialPos::corners_in_world`].
+pub fn nearest_vertex(
+    world_pos: Vec2,
+    grid_size: &TilemapGridSize,
+    hex_coord_sys: HexCoordSystem,
+) -> (Vec2, VertexId) {
+    let is_row = matches!(
+        hex_coord_sys,
+        HexCoordSystem::Row | HexCoordSystem::RowEven | HexCoordSystem::RowOdd
+    );
+    let tile = if is_row {
+        AxialPos::from_world_pos_row(&world_pos, grid_size)
+    } else {
+        AxialPos::from_world_pos_col(&world_pos, grid_size)
+    };
+
+    let corners = tile.corners_in_world(grid_size, hex_coord_sys);
+    let (index, corner) = corners
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            a.distance_squared(world_pos)
+                .partial_cmp(&b.distance_squared(world_pos))
+                .unwrap_or(Ordering::Equal)
+        })
+        .unwrap();
+
+    // For row oriented hexes, corner `i` lies between the neighbors in directions `i` and `i + 1`,
+    // while for column oriented hexes, it lies between directions `i - 1` and `i`.
+    let direction = HexDirection::from(index);
+    let vertex_id = if is_row {
+        VertexId::new(tile, direction, direction + 1)
+    } else {
+        VertexId::new(tile, direction - 1, direction)
+    };
+
+    (*corner, vertex_id)
+}
+
+/// Returns the tiles lying along `arms` logarithmic spiral arms, centered on the middle tile of the
+/// map, with each arm sampled at `points_per_arm` evenly spaced angles and snapped to the tile
+/// containing each sample.
+///
+/// Each arm winds `turns` times counter-clockwise around the center, and the arms are rotated
+/// evenly about it. At an angle `theta` from its start, an arm lies `scale * (e^(theta / 2pi) - 1)`
+/// world units away from the center, so every arm starts at the center and grows by a factor of
+/// about `e` per turn.
+///
+/// Tiles are returned in the order they are first reached, arm by arm. Duplicate tiles, and
+/// samples lying outside of `map_size`, are dropped.
+pub fn spiral_arms(
+    arms: u32,
+    turns: f32,
+    points_per_arm: u32,
+    scale: f32,
+    hex_coord_sys: HexCoordSystem,
+    grid_size: &TilemapGridSize,
+    map_size: &TilemapSize,
+) -> Vec<TilePos> {
+    let map_type = TilemapType::Hexagon(hex_coord_sys);
+    let center = TilePos {
+        x: map_size.x / 2,
+        y: map_size.y / 2,
+    }
+    .center_in_world(grid_size, &map_type);
+
+    let mut seen = HashSet::default();
+    let mut tiles = Vec::new();
+    for arm in 0..arms {
+        let arm_angle = arm as f32 * TAU / arms as f32;
+        for i in 0..points_per_arm {
+            let t = if points_per_arm > 1 {
+                i as f32 / (points_per_arm - 1) as f32
+            } else {
+                0.0
+            };
+            let theta = t * turns * TAU;
+            let radius = scale * ((theta / TAU).exp() - 1.0);
+            let world_pos = center + radius * Vec2::from_angle(arm_angle + theta);
+            if let Some(tile_pos) =
+                TilePos::from_world_pos(&world_pos, map_size, grid_size, &map_type)
+            {
+                if seen.insert(tile_pos) {
+                    tiles.push(tile_pos);
+                }
+            }
+        }
+    }
+    tiles
+}
+
+/// Returns `true` if `a`, `b` and `c` all lie on a common straight line of hexes running along
+/// one of the three hex axes, i.e. if they share their `q`, `r`, or `s` cube coordinate.
+///
+/// Along such a line, [`AxialPos::line_to`] steps in a single [`HexDirection`].
+pub fn are_collinear(a: &AxialPos, b: &AxialPos, c: &AxialPos) -> bool {
+    let s = |tile: &AxialPos| -tile.q - tile.r;
+    (a.q == b.q && b.q == c.q) || (a.r == b.r && b.r == c.r) || (s(a) == s(b) && s(b) == s(c))
+}
+
+/// Returns `path` with the intermediate hexes of each straight run removed, keeping both
+/// endpoints of `path` and every hex at which it turns.
+///
+/// A hex is removed if it is [collinear](are_collinear) with, and lies between, the hexes kept
+/// before it and after it. For a path whose consecutive hexes are adjacent, joining the kept hexes
+/// with [`AxialPos::line_to`] therefore gives back the original path.
+pub fn simplify_path(path: &[AxialPos]) -> Vec<AxialPos> {
+    let mut simplified: Vec<AxialPos> = Vec::with_capacity(path.len());
+    for tile in path.iter() {
+        if simplified.len() >= 2 {
+            let (a, b) = (
+                simplified[simplified.len() - 2],
+                simplified[simplified.len() - 1],
+            );
+            let between = a.distance_from(&b) + b.distance_from(tile) == a.distance_from(tile);
+            if between && are_collinear(&a, &b, tile) {
+                simplified.pop();
+            }
+        }
+        simplified.push(*tile);
+    }
+    simplified
+}
+
+/// Returns the hexes visited by taking `length` single steps from `start`, alternating between
+/// `dir_a` and `dir_b`, starting with `dir_a`.
+///
+/// The result includes `start`, so it contains `length + 1` hexes. When `dir_a` and `dir_b` are
+/// adjacent directions, this produces a zig-zag running halfway between them, `pi/6` away from
+/// each.
+pub fn staircase(
+    start: AxialPos,
+    dir_a: HexDirection,
+    dir_b: HexDirection,
+    length: u32,
+) -> Vec<AxialPos> {
+    let mut tiles = Vec::with_capacity(length as usize + 1);
+    let mut current = start;
+    tiles.push(current);
+    for step in 0..length {
+        let direction = if step % 2 == 0 { dir_a } else { dir_b };
+        current = current + HEX_OFFSETS[direction as usize];
+        tiles.push(current);
+    }
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP_SIZE: TilemapSize = TilemapSize { x: 8, y: 8 };
+
+    #[test]
+    fn cover_fraction_averages_intermediate_cover() {
+        let (from, to) = (TilePos { x: 0, y: 2 }, TilePos { x: 4, y: 2 });
+        let clear = cover_fraction(from, to, |_| 0.0, HexCoordSystem::Row, &MAP_SIZE);
+        assert_eq!(clear, 0.0);
+
+        let wall = TilePos { x: 2, y: 2 };
+        let covered = cover_fraction(
+            from,
+            to,
+            |tile_pos| if tile_pos == wall { 1.0 } else { 0.0 },
+            HexCoordSystem::Row,
+            &MAP_SIZE,
+        );
+        assert!((covered - 1.0 / 3.0).abs() < 1e-6);
+
+        let ends_only = cover_fraction(
+            from,
+            to,
+            |tile_pos| {
+                if tile_pos == from || tile_pos == to {
+                    1.0
+                } else {
+                    0.0
+                }
+            },
+            HexCoordSystem::Row,
+            &MAP_SIZE,
+        );
+        assert_eq!(ends_only, 0.0);
+    }
+
+    #[test]
+    fn thick_line_of_width_one_dilates_the_thin_line() {
+        let (a, b) = (AxialPos { q: -2, r: 1 }, AxialPos { q: 3, r: -1 });
+        let thin = a.line_to(&b);
+
+        let mut dilated: HashSet<AxialPos> = thin.iter().copied().collect();
+        for axial_pos in thin.iter() {
+            dilated.extend(axial_pos.neighbors());
+        }
+        assert_eq!(thick_line(a, b, 1), dilated);
+        assert_eq!(thick_line(a, b, 0), thin.into_iter().collect());
+    }
+
+    #[test]
+    fn parallel_line_offsets() {
+        let (a, b) = (AxialPos { q: 0, r: 0 }, AxialPos { q: 5, r: -2 });
+        let line = a.line_to(&b);
+        assert_eq!(parallel_line(a, b, 0), line);
+
+        for offset in [1, -1] {
+            let shifted = parallel_line(a, b, offset);
+            assert_eq!(shifted.len(), line.len());
+            for (original, moved) in line.iter().zip(shifted.iter()) {
+                assert_eq!(original.distance_from(moved), 1);
+            }
+            assert!(shifted.iter().all(|tile| !line.contains(tile)));
+        }
+    }
+
+    #[test]
+    fn supercover_line_includes_both_hexes_along_a_diagonal() {
+        let grid_size = TilemapGridSize { x: 16.0, y: 16.0 };
+        // The segment towards a diagonal neighbor runs along the edge shared by the two hexes in
+        // between, touching both of them.
+        let (a, b) = (AxialPos { q: 0, r: 0 }, AxialPos { q: 1, r: 1 });
+        let cover = supercover_line(a, b, &grid_size, HexCoordSystem::Row);
+        assert_eq!(cover.len(), 4);
+        assert_eq!(cover.first(), Some(&a));
+        assert_eq!(cover.last(), Some(&b));
+        assert!(cover.contains(&AxialPos { q: 1, r: 0 }));
+        assert!(cover.contains(&AxialPos { q: 0, r: 1 }));
+        assert_eq!(a.line_to(&b).len(), 3);
+    }
+
+    #[test]
+    fn nearest_vertex_snaps_to_the_shared_vertex() {
+        let grid_size = TilemapGridSize { x: 16.0, y: 16.0 };
+        for hex_coord_sys in [HexCoordSystem::Row, HexCoordSystem::Column] {
+            let tile = AxialPos { q: 1, r: -2 };
+            for corner in tile.corners_in_world(&grid_size, hex_coord_sys) {
+                let (snapped, vertex_id) = nearest_vertex(corner, &grid_size, hex_coord_sys);
+                assert!(snapped.distance(corner) < 1e-3);
+
+                // Approaching the vertex from inside each of the hexes sharing it gives the same
+                // vertex.
+                for sharing in vertex_id.tiles() {
+                    let center =
+                        sharing.center_in_world_given_coord_system(&grid_size, hex_coord_sys);
+                    let near = corner + 0.1 * (center - corner);
+                    let (snapped, other_id) = nearest_vertex(near, &grid_size, hex_coord_sys);
+                    assert!(snapped.distance(corner) < 1e-3);
+                    assert_eq!(other_id, vertex_id);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn spiral_arms_gives_one_tip_per_arm_in_bounds() {
+        let grid_size = TilemapGridSize { x: 16.0, y: 16.0 };
+        let map_size = TilemapSize { x: 15, y: 15 };
+        let center = TilePos { x: 7, y: 7 };
+        for arms in [1, 3, 6] {
+            // With two samples per arm, each arm is its start at the center, and its tip.
+            let tiles = spiral_arms(
+                arms,
+                0.25,
+                2,
+                100.0,
+                HexCoordSystem::Row,
+                &grid_size,
+                &map_size,
+            );
+            assert_eq!(tiles.len() as u32, arms + 1);
+            assert_eq!(tiles[0], center);
+        }
+
+        // Arms growing past the edge of the map are cut off.
+        let tiles = spiral_arms(
+            4,
+            3.0,
+            200,
+            20.0,
+            HexCoordSystem::Row,
+            &grid_size,
+            &map_size,
+        );
+        assert!(!tiles.is_empty());
+        assert!(tiles
+            .iter()
+            .all(|tile_pos| tile_pos.within_map_bounds(&map_size)));
+        let unique: HashSet<TilePos> = tiles.iter().copied().collect();
+        assert_eq!(unique.len(), tiles.len());
+    }
+
+    #[test]
+    fn simplify_path_of_straight_and_bent_paths() {
+        let (start, corner, end) = (
+            AxialPos { q: 0, r: 0 },
+            AxialPos { q: 3, r: 0 },
+            AxialPos { q: 3, r: 3 },
+        );
+        let straight = start.line_to(&corner);
+        assert_eq!(simplify_path(&straight), vec![start, corner]);
+
+        let mut bent = straight.clone();
+        bent.extend(corner.line_to(&end).into_iter().skip(1));
+        assert_eq!(simplify_path(&bent), vec![start, corner, end]);
+
+        assert_eq!(simplify_path(&[start]), vec![start]);
+        assert!(simplify_path(&[]).is_empty());
+    }
+
+    #[test]
+    fn staircase_alternates_between_the_directions() {
+        let start = AxialPos { q: 1, r: -1 };
+        let stairs = staircase(start, HexDirection::Zero, HexDirection::One, 5);
+        assert_eq!(stairs.len(), 6);
+        assert_eq!(stairs[0], start);
+        for (step, pair) in stairs.windows(2).enumerate() {
+            let expected = if step % 2 == 0 {
+                HexDirection::Zero
+            } else {
+                HexDirection::One
+            };
+            assert_eq!(pair[1] - pair[0], HEX_OFFSETS[expected as usize]);
+        }
+
+        assert_eq!(
+            staircase(start, HexDirection::Two, HexDirection::Three, 0),
+            vec![start]
+        );
+    }
+}
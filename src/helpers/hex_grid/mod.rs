@@ -1,5 +1,32 @@
+//! Helpers for working with hexagonal grids.
+//!
+//! Most computations are carried out in axial coordinates (see [`axial::AxialPos`]). Helpers which
+//! accept or return [`TilePos`](crate::tiles::TilePos) take the
+//! [`HexCoordSystem`](crate::map::HexCoordSystem) of the map, and convert tile positions to and
+//! from axial coordinates with [`axial::AxialPos::from_tile_pos_given_coord_system`] and
+//! [`axial::AxialPos::as_tile_pos_given_coord_system`].
+
 pub mod axial;
 pub mod consts;
 pub mod cube;
+pub mod edge;
+mod field;
+mod graph;
+mod line;
 pub mod neighbors;
 pub mod offset;
+mod pathfinding;
+mod region;
+mod shapes;
+mod symmetry;
+mod visibility;
+
+pub use field::*;
+pub use graph::*;
+pub use line::*;
+pub use neighbors::combined_zoc;
+pub use pathfinding::*;
+pub use region::*;
+pub use shapes::*;
+pub use symmetry::*;
+pub use visibility::*;
@@ -1,4 +1,10 @@
-use crate::helpers::hex_grid::axial::AxialPos;
+use crate::helpers::hex_grid::axial::{AxialPos, ROW_BASIS};
+use crate::map::HexCoordSystem;
+use crate::tiles::TilePos;
+use crate::TilemapSize;
+use bevy::math::Vec2;
+use bevy::utils::HashSet;
+use std::f32::consts::PI;
 use std::ops::{Add, Sub};
 
 /// Neighbors of a hexagonal tile. `Zero` corresponds with `East` for row-oriented tiles, and
@@ -12,7 +18,7 @@ use std::ops::{Add, Sub};
 /// modulo 6).
 ///
 /// [`HexDirection`]s can be converted from/into `usize`, `u32`, `isize`, `i32`.
-#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum HexDirection {
     Zero,
     One,
@@ -42,6 +48,30 @@ pub const HEX_OFFSETS: [AxialPos; 6] = [
     AxialPos { q: 1, r: -1 },
 ];
 
+impl HexDirection {
+    /// Returns the direction whose world space angle is nearest to `angle` (in radians, measured
+    /// counter-clockwise from the positive `x` axis), along with the signed residual angle, such
+    /// that `angle` is the direction's angle plus the residual (modulo `2 * pi`).
+    ///
+    /// Directions are laid out as by [`ROW_BASIS`] for row-oriented coordinate systems, where
+    /// [`HexDirection::Zero`] lies at angle `0`, and as by
+    /// [`COL_BASIS`](crate::helpers::hex_grid::axial::COL_BASIS) for column-oriented ones, where
+    /// it lies at angle `pi/6`. The residual lies in `[-pi/6, pi/6)`, so angles lying exactly
+    /// halfway between two directions resolve to the counter-clockwise one.
+    pub fn nearest_to_angle(angle: f32, hex_coord_sys: HexCoordSystem) -> (HexDirection, f32) {
+        let offset = match hex_coord_sys {
+            HexCoordSystem::Row | HexCoordSystem::RowEven | HexCoordSystem::RowOdd => 0.0,
+            HexCoordSystem::Column | HexCoordSystem::ColumnEven | HexCoordSystem::ColumnOdd => {
+                PI / 6.0
+            }
+        };
+        let sectors = (angle - offset) / (PI / 3.0);
+        let nearest = (sectors + 0.5).floor();
+        let residual = (sectors - nearest) * (PI / 3.0);
+        ((nearest as isize).into(), residual)
+    }
+}
+
 impl From<usize> for HexDirection {
     fn from(choice: usize) -> Self {
         let ix = choice % 6;
@@ -201,3 +231,161 @@ impl From<HexColDirection> for HexDirection {
         (direction as usize).into()
     }
 }
+
+impl AxialPos {
+    /// Returns the positions of the six neighbors of `self`, ordered by [`HexDirection`].
+    pub fn neighbors(&self) -> [AxialPos; 6] {
+        HEX_OFFSETS.map(|offset| *self + offset)
+    }
+
+    /// Returns the six neighbors of `self`, sorted in ascending order of their distance from
+    /// `goal`. Neighbors which are equally far from `goal` retain their [`HexDirection`] order.
+    ///
+    /// The first element is a neighbor which is strictly closer to `goal` than `self`, unless
+    /// `self` is `goal`.
+    pub fn neighbors_toward(&self, goal: &AxialPos) -> [AxialPos; 6] {
+        let mut neighbors = self.neighbors();
+        neighbors.sort_by_key(|neighbor| neighbor.distance_from(goal));
+        neighbors
+    }
+
+    /// Returns the neighbors of `self` lying between directions `from` and `to` (inclusive),
+    /// walking clockwise from `from` to `to`.
+    ///
+    /// If `from == to`, only the neighbor in that direction is returned.
+    pub fn neighbor_fan(&self, from: HexDirection, to: HexDirection) -> Vec<AxialPos> {
+        let mut fan = vec![*self + HEX_OFFSETS[from as usize]];
+        let mut direction = from;
+        while direction != to {
+            direction = direction - 1;
+            fan.push(*self + HEX_OFFSETS[direction as usize]);
+        }
+        fan
+    }
+
+    /// Returns the zone of control of a unit standing on `self`: the neighbors of `self` which lie
+    /// within `map_size`, ordered by [`HexDirection`].
+    pub fn zone_of_control(
+        &self,
+        hex_coord_sys: HexCoordSystem,
+        map_size: &TilemapSize,
+    ) -> Vec<TilePos> {
+        self.neighbors()
+            .iter()
+            .filter_map(|neighbor| neighbor.as_tile_pos_given_coord_system(map_size, hex_coord_sys))
+            .collect()
+    }
+
+    /// Returns the [`HexDirection`] of the `pi/3` sector around `origin` which `self` lies in.
+    ///
+    /// Each sector is centered on the line from `origin` through its neighbor in the sector's
+    /// direction. Returns [`HexDirection::Zero`] if `self` is `origin`.
+    pub fn sector_from(&self, origin: &AxialPos) -> HexDirection {
+        let delta = *self - *origin;
+        let v = ROW_BASIS * Vec2::new(delta.q as f32, delta.r as f32);
+        let sector = (v.y.atan2(v.x) / (PI / 3.0)).round() as i32;
+        sector.into()
+    }
+}
+
+/// Returns the union of the zones of control (see [`AxialPos::zone_of_control`]) of all `units`.
+///
+/// Each tile appears once, in the order in which it is first found. Tiles occupied by units are
+/// included only if they lie in the zone of control of another unit.
+pub fn combined_zoc(
+    units: &[AxialPos],
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> Vec<TilePos> {
+    let mut seen = HashSet::default();
+    units
+        .iter()
+        .flat_map(|unit| unit.zone_of_control(hex_coord_sys, map_size))
+        .filter(|tile_pos| seen.insert(*tile_pos))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::TAU;
+
+    #[test]
+    fn neighbors_toward_starts_with_a_strictly_closer_neighbor() {
+        let origin = AxialPos { q: 0, r: 0 };
+        for goal in origin.range(4) {
+            if goal == origin {
+                continue;
+            }
+            let neighbors = origin.neighbors_toward(&goal);
+            assert!(neighbors[0].distance_from(&goal) < origin.distance_from(&goal));
+            for pair in neighbors.windows(2) {
+                assert!(pair[0].distance_from(&goal) <= pair[1].distance_from(&goal));
+            }
+        }
+    }
+
+    #[test]
+    fn neighbor_fan_walks_clockwise() {
+        let center = AxialPos { q: 1, r: 2 };
+        let fan = center.neighbor_fan(HexDirection::Two, HexDirection::Zero);
+        let expected: Vec<AxialPos> = [HexDirection::Two, HexDirection::One, HexDirection::Zero]
+            .iter()
+            .map(|direction| center + HEX_OFFSETS[*direction as usize])
+            .collect();
+        assert_eq!(fan, expected);
+
+        let wrapped = center.neighbor_fan(HexDirection::Zero, HexDirection::Four);
+        assert_eq!(wrapped.len(), 3);
+        assert_eq!(
+            wrapped[1],
+            center + HEX_OFFSETS[HexDirection::Five as usize]
+        );
+
+        let single = center.neighbor_fan(HexDirection::Three, HexDirection::Three);
+        assert_eq!(single, vec![center + HEX_OFFSETS[3]]);
+    }
+
+    #[test]
+    fn nearest_to_angle_boundaries_and_residuals() {
+        // Halfway between two directions resolves to the counter-clockwise one.
+        let (direction, residual) = HexDirection::nearest_to_angle(PI / 6.0, HexCoordSystem::Row);
+        assert_eq!(direction, HexDirection::One);
+        assert!((residual + PI / 6.0).abs() < 1e-6);
+        let (direction, _) = HexDirection::nearest_to_angle(0.0, HexCoordSystem::Column);
+        assert_eq!(direction, HexDirection::Zero);
+        let (direction, _) = HexDirection::nearest_to_angle(-PI / 6.0, HexCoordSystem::Row);
+        assert_eq!(direction, HexDirection::Zero);
+
+        for hex_coord_sys in [HexCoordSystem::Row, HexCoordSystem::Column] {
+            let offset = match hex_coord_sys {
+                HexCoordSystem::Row => 0.0,
+                _ => PI / 6.0,
+            };
+            for step in -100..100 {
+                let angle = step as f32 * 0.07;
+                let (direction, residual) = HexDirection::nearest_to_angle(angle, hex_coord_sys);
+                assert!(residual.abs() <= PI / 6.0 + 1e-5);
+                let direction_angle = offset + direction as usize as f32 * PI / 3.0;
+                let difference = (angle - direction_angle - residual).rem_euclid(TAU);
+                assert!(difference < 1e-4 || TAU - difference < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn zone_of_control_of_a_corner_unit() {
+        let map_size = TilemapSize { x: 4, y: 4 };
+        let zone = |q, r| AxialPos { q, r }.zone_of_control(HexCoordSystem::Row, &map_size);
+
+        assert_eq!(
+            zone(0, 0),
+            vec![TilePos { x: 1, y: 0 }, TilePos { x: 0, y: 1 }]
+        );
+        assert_eq!(
+            zone(3, 3),
+            vec![TilePos { x: 2, y: 3 }, TilePos { x: 3, y: 2 }]
+        );
+        assert_eq!(zone(1, 1).len(), 6);
+    }
+}
@@ -0,0 +1,742 @@
+use crate::helpers::hex_grid::axial::AxialPos;
+use crate::helpers::hex_grid::neighbors::{HexDirection, HEX_OFFSETS};
+use crate::map::HexCoordSystem;
+use crate::tiles::TilePos;
+use crate::TilemapSize;
+use bevy::utils::{HashMap, HashSet};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::hash::Hash;
+
+/// A cost accumulated by [`uniform_cost_search`].
+trait Cost: Copy + Default + PartialOrd {
+    /// Adds two costs, saturating at the greatest representable cost instead of overflowing.
+    fn saturating_add(self, other: Self) -> Self;
+}
+
+impl Cost for u32 {
+    fn saturating_add(self, other: Self) -> Self {
+        u32::saturating_add(self, other)
+    }
+}
+
+impl Cost for f32 {
+    fn saturating_add(self, other: Self) -> Self {
+        self + other
+    }
+}
+
+/// A state in the frontier of [`uniform_cost_search`], ordered so that the state with the lowest
+/// estimated total cost is the greatest, and is therefore popped first from a [`BinaryHeap`]. Ties
+/// are broken in favor of the smallest state.
+#[derive(PartialEq)]
+struct Visit<C, S> {
+    estimate: C,
+    cost: C,
+    state: S,
+}
+
+impl<C: PartialOrd, S: Ord> Eq for Visit<C, S> {}
+
+impl<C: PartialOrd, S: Ord> Ord for Visit<C, S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .partial_cmp(&self.estimate)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.state.cmp(&self.state))
+    }
+}
+
+impl<C: PartialOrd, S: Ord> PartialOrd for Visit<C, S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Runs a uniform cost search (Dijkstra's algorithm) from `start`, and returns the minimal cost of
+/// reaching every state found.
+///
+/// `successors(state, steps)` pushes each state reachable in one step from `state` onto `steps`,
+/// along with the cost of that step. States whose cost would exceed `budget` are not reached. The
+/// search stops early once a state for which `stop` returns `true` is settled, in which case the
+/// costs of states which are not settled yet may not be minimal. If `parents` is provided, it is
+/// filled with the predecessor of every state reached, so that a path can be reconstructed.
+///
+/// States are settled in order of their cost plus `heuristic(state)`. A heuristic of zero gives a
+/// plain uniform cost search, while a consistent heuristic (one which never decreases by more than
+/// the cost of a step) turns it into A*.
+fn uniform_cost_search<C, S>(
+    start: S,
+    budget: Option<C>,
+    mut successors: impl FnMut(S, &mut Vec<(S, C)>),
+    heuristic: impl Fn(S) -> C,
+    mut stop: impl FnMut(S) -> bool,
+    mut parents: Option<&mut HashMap<S, S>>,
+) -> HashMap<S, C>
+where
+    C: Cost,
+    S: Copy + Eq + Hash + Ord,
+{
+    let mut costs = HashMap::default();
+    let mut frontier = BinaryHeap::new();
+    let mut steps = Vec::new();
+
+    costs.insert(start, C::default());
+    frontier.push(Visit {
+        estimate: heuristic(start),
+        cost: C::default(),
+        state: start,
+    });
+
+    while let Some(Visit { cost, state, .. }) = frontier.pop() {
+        match costs.get(&state) {
+            Some(best) if *best < cost => continue,
+            _ => {}
+        }
+        if stop(state) {
+            break;
+        }
+
+        successors(state, &mut steps);
+        for (next_state, step_cost) in steps.drain(..) {
+            let next_cost = cost.saturating_add(step_cost);
+            match budget {
+                Some(budget) if next_cost > budget => continue,
+                _ => {}
+            }
+            match costs.get(&next_state) {
+                Some(best) if *best <= next_cost => {}
+                _ => {
+                    costs.insert(next_state, next_cost);
+                    if let Some(parents) = parents.as_deref_mut() {
+                        parents.insert(next_state, state);
+                    }
+                    frontier.push(Visit {
+                        estimate: next_cost.saturating_add(heuristic(next_state)),
+                        cost: next_cost,
+                        state: next_state,
+                    });
+                }
+            }
+        }
+    }
+
+    costs
+}
+
+/// Returns every `(tile, facing)` state reachable from `start` while facing `facing`, mapped to
+/// the minimal movement cost of reaching it, without exceeding `budget`.
+///
+/// From any state, a unit may either step forward into the neighboring tile in its facing
+/// direction for `step_cost`, or turn by one [`HexDirection`] (`pi/3`) in either direction for
+/// `turn_cost`. Tiles which are not `passable`, or which lie outside of `map_size`, cannot be
+/// stepped into.
+#[allow(clippy::too_many_arguments)]
+pub fn reachable_with_facing(
+    start: TilePos,
+    facing: HexDirection,
+    budget: u32,
+    step_cost: u32,
+    turn_cost: u32,
+    passable: impl Fn(TilePos) -> bool,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashMap<(TilePos, HexDirection), u32> {
+    let start_axial = AxialPos::from_tile_pos_given_coord_system(&start, hex_coord_sys);
+    let costs = uniform_cost_search(
+        (start_axial, facing),
+        Some(budget),
+        |(axial_pos, facing), steps| {
+            steps.push(((axial_pos, facing + 1), turn_cost));
+            steps.push(((axial_pos, facing - 1), turn_cost));
+            let forward = axial_pos + HEX_OFFSETS[facing as usize];
+            if let Some(tile_pos) = forward.as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+            {
+                if passable(tile_pos) {
+                    steps.push(((forward, facing), step_cost));
+                }
+            }
+        },
+        |_| 0,
+        |_| false,
+        None,
+    );
+
+    // Every tile other than `start` was checked to lie within the map when it was stepped into.
+    costs
+        .into_iter()
+        .map(|((axial_pos, facing), cost)| {
+            let tile_pos = axial_pos
+                .as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+                .unwrap_or(start);
+            ((tile_pos, facing), cost)
+        })
+        .collect()
+}
+
+/// Runs an A* search from `start`, stopping once `goal` is settled. The heuristic is the hex
+/// distance to `goal` multiplied by `min_step_cost`.
+///
+/// If `parents` is provided, it is filled with the predecessor of every tile reached, so that a
+/// path can be reconstructed. Returns the minimal cost of reaching `goal`, if it is reachable.
+fn search(
+    start: TilePos,
+    goal: TilePos,
+    cost: &impl Fn(TilePos, TilePos) -> Option<u32>,
+    min_step_cost: u32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+    parents: Option<&mut HashMap<AxialPos, AxialPos>>,
+) -> Option<u32> {
+    let start_axial = AxialPos::from_tile_pos_given_coord_system(&start, hex_coord_sys);
+    let goal_axial = AxialPos::from_tile_pos_given_coord_system(&goal, hex_coord_sys);
+    let costs = uniform_cost_search(
+        start_axial,
+        None,
+        |axial_pos, steps| {
+            let tile_pos = if axial_pos == start_axial {
+                start
+            } else {
+                axial_pos
+                    .as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+                    .unwrap()
+            };
+            for neighbor in axial_pos.neighbors() {
+                let neighbor_tile_pos =
+                    match neighbor.as_tile_pos_given_coord_system(map_size, hex_coord_sys) {
+                        Some(neighbor_tile_pos) => neighbor_tile_pos,
+                        None => continue,
+                    };
+                if let Some(step_cost) = cost(tile_pos, neighbor_tile_pos) {
+                    steps.push((neighbor, step_cost));
+                }
+            }
+        },
+        |axial_pos| (axial_pos.distance_from(&goal_axial) as u32).saturating_mul(min_step_cost),
+        |axial_pos| axial_pos == goal_axial,
+        parents,
+    );
+    costs.get(&goal_axial).copied()
+}
+
+/// Returns the cheapest path from `start` to `goal` (including both), along with its total cost.
+///
+/// `cost(from, to)` gives the cost of stepping from a tile into its neighbor, or `None` if the
+/// step is not allowed. Only tiles within `map_size` are considered. The search is guided by the
+/// hex distance to `goal` multiplied by `min_step_cost`, which must not exceed the cost of any
+/// allowed step for the path to be optimal. A `min_step_cost` of `0` makes the search equivalent
+/// to Dijkstra's algorithm.
+///
+/// Returns `None` if `goal` cannot be reached.
+pub fn astar(
+    start: TilePos,
+    goal: TilePos,
+    cost: impl Fn(TilePos, TilePos) -> Option<u32>,
+    min_step_cost: u32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> Option<(Vec<TilePos>, u32)> {
+    let mut parents = HashMap::default();
+    let total_cost = search(
+        start,
+        goal,
+        &cost,
+        min_step_cost,
+        hex_coord_sys,
+        map_size,
+        Some(&mut parents),
+    )?;
+
+    // Every tile of the path other than `start` was checked to lie within the map when it was
+    // reached.
+    let start_axial = AxialPos::from_tile_pos_given_coord_system(&start, hex_coord_sys);
+    let mut path = Vec::new();
+    let mut current = AxialPos::from_tile_pos_given_coord_system(&goal, hex_coord_sys);
+    while current != start_axial {
+        path.push(
+            current
+                .as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+                .unwrap(),
+        );
+        current = parents[&current];
+    }
+    path.push(start);
+    path.reverse();
+
+    Some((path, total_cost))
+}
+
+/// Wraps `base_cost` into a step cost function suitable for [`astar`], which adds a penalty of
+/// `danger(to) * penalty_scale` (rounded to the nearest integer) to every step into a tile `to`.
+///
+/// Dangerous tiles are discouraged rather than blocked: the cheapest path only crosses them when
+/// going around costs more than the penalty. Negative penalties are treated as `0`, penalties too
+/// large for a `u32` saturate at [`u32::MAX`], and steps which `base_cost` does not allow remain
+/// disallowed.
+pub fn danger_weighted_cost(
+    base_cost: impl Fn(TilePos, TilePos) -> Option<u32>,
+    danger: impl Fn(TilePos) -> f32,
+    penalty_scale: f32,
+) -> impl Fn(TilePos, TilePos) -> Option<u32> {
+    move |from, to| {
+        let penalty = (danger(to) * penalty_scale).round().max(0.0) as u32;
+        base_cost(from, to).map(|step_cost| step_cost.saturating_add(penalty))
+    }
+}
+
+/// Returns the cost of the cheapest path from `start` to `goal`, without reconstructing the path
+/// itself.
+///
+/// This is equivalent to the cost returned by [`astar`], but does not keep track of the
+/// predecessors of each tile.
+///
+/// Returns `None` if `goal` cannot be reached.
+pub fn path_cost(
+    start: TilePos,
+    goal: TilePos,
+    cost: impl Fn(TilePos, TilePos) -> Option<u32>,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> Option<u32> {
+    search(start, goal, &cost, 0, hex_coord_sys, map_size, None)
+}
+
+/// Returns the cheapest path from `start` to `goal` (see [`astar`]) under the zone of control
+/// rule: entering a tile flagged by `zoc` ends movement, so such a tile can only be the final step
+/// of the path.
+///
+/// A unit which starts in a zone of control may still leave it. Returns `None` if `goal` cannot be
+/// reached.
+pub fn astar_with_zoc(
+    start: TilePos,
+    goal: TilePos,
+    cost: impl Fn(TilePos, TilePos) -> Option<u32>,
+    min_step_cost: u32,
+    zoc: impl Fn(TilePos) -> bool,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> Option<Vec<TilePos>> {
+    let (path, _) = astar(
+        start,
+        goal,
+        |from, to| {
+            if from != start && zoc(from) {
+                None
+            } else {
+                cost(from, to)
+            }
+        },
+        min_step_cost,
+        hex_coord_sys,
+        map_size,
+    )?;
+    Some(path)
+}
+
+/// Returns the tiles of a corridor from `start` to `goal`: a shortest path through `passable`
+/// tiles (see [`astar`]), widened to include every tile within `radius` of it.
+///
+/// Only `passable` tiles within `map_size` are included, except for the path's own endpoints. A
+/// `radius` of `0` returns just the path. Returns an empty set if `goal` cannot be reached.
+pub fn corridor(
+    start: TilePos,
+    goal: TilePos,
+    radius: u32,
+    passable: impl Fn(TilePos) -> bool,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashSet<TilePos> {
+    let path = match astar(
+        start,
+        goal,
+        |_, to| {
+            if to == goal || passable(to) {
+                Some(1)
+            } else {
+                None
+            }
+        },
+        1,
+        hex_coord_sys,
+        map_size,
+    ) {
+        Some((path, _)) => path,
+        None => return HashSet::default(),
+    };
+
+    let mut tiles: HashSet<TilePos> = path.iter().copied().collect();
+    for tile_pos in path.iter() {
+        let axial_pos = AxialPos::from_tile_pos_given_coord_system(tile_pos, hex_coord_sys);
+        for nearby in axial_pos.range(radius) {
+            if let Some(nearby) = nearby.as_tile_pos_given_coord_system(map_size, hex_coord_sys) {
+                if passable(nearby) {
+                    tiles.insert(nearby);
+                }
+            }
+        }
+    }
+    tiles
+}
+
+/// Returns the cheapest path which visits each of `waypoints` in order, by chaining the paths
+/// found by [`astar`] between consecutive waypoints.
+///
+/// The waypoint shared by two consecutive legs appears only once. A single waypoint gives a path
+/// containing just that waypoint, and no waypoints give an empty path.
+///
+/// Returns `None` if any leg cannot be completed.
+pub fn multi_waypoint_path(
+    waypoints: &[TilePos],
+    cost: impl Fn(TilePos, TilePos) -> Option<u32>,
+    min_step_cost: u32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> Option<Vec<TilePos>> {
+    let mut path: Vec<TilePos> = waypoints.first().copied().into_iter().collect();
+    for leg in waypoints.windows(2) {
+        let (leg_path, _) = astar(
+            leg[0],
+            leg[1],
+            &cost,
+            min_step_cost,
+            hex_coord_sys,
+            map_size,
+        )?;
+        path.extend(leg_path.into_iter().skip(1));
+    }
+    Some(path)
+}
+
+/// Returns every tile which can be reached from `start` within `budget` time, mapped to the least
+/// time needed to reach it.
+///
+/// `enter_time(tile)` gives the time needed to step into `tile` from any of its neighbors, or
+/// `None` if it is impassable. Times are expected to be non-negative. `start` is always reached,
+/// in a time of `0.0`. Only tiles within `map_size` are considered.
+pub fn reachable_time(
+    start: TilePos,
+    budget: f32,
+    enter_time: impl Fn(TilePos) -> Option<f32>,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashMap<TilePos, f32> {
+    let start_axial = AxialPos::from_tile_pos_given_coord_system(&start, hex_coord_sys);
+    let times = uniform_cost_search(
+        start_axial,
+        Some(budget),
+        |axial_pos, steps| {
+            for neighbor in axial_pos.neighbors() {
+                if let Some(tile_pos) =
+                    neighbor.as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+                {
+                    if let Some(step_time) = enter_time(tile_pos) {
+                        steps.push((neighbor, step_time));
+                    }
+                }
+            }
+        },
+        |_| 0.0,
+        |_| false,
+        None,
+    );
+
+    // Every tile other than `start` was checked to lie within the map when it was reached.
+    times
+        .into_iter()
+        .map(|(axial_pos, time)| {
+            let tile_pos = axial_pos
+                .as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+                .unwrap_or(start);
+            (tile_pos, time)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP_SIZE: TilemapSize = TilemapSize { x: 8, y: 8 };
+
+    #[test]
+    fn corridor_of_radius_one_dilates_the_path() {
+        let (start, goal) = (TilePos { x: 0, y: 3 }, TilePos { x: 7, y: 3 });
+        let passable = |tile_pos: TilePos| tile_pos.x != 4 || tile_pos.y == 6;
+        let (path, _) = astar(
+            start,
+            goal,
+            |_, to| if passable(to) { Some(1) } else { None },
+            1,
+            HexCoordSystem::Row,
+            &MAP_SIZE,
+        )
+        .unwrap();
+
+        let mut expected: HashSet<TilePos> = path.iter().copied().collect();
+        for tile_pos in path.iter() {
+            for neighbor in AxialPos::from(tile_pos).neighbors() {
+                if let Some(neighbor) = neighbor.as_tile_pos(&MAP_SIZE) {
+                    if passable(neighbor) {
+                        expected.insert(neighbor);
+                    }
+                }
+            }
+        }
+        assert_eq!(
+            corridor(start, goal, 1, passable, HexCoordSystem::Row, &MAP_SIZE),
+            expected
+        );
+    }
+
+    #[test]
+    fn corridor_exempts_both_endpoints() {
+        let (start, goal) = (TilePos { x: 0, y: 0 }, TilePos { x: 3, y: 0 });
+        let passable = |tile_pos: TilePos| tile_pos != start && tile_pos != goal;
+        let tiles = corridor(start, goal, 0, passable, HexCoordSystem::Row, &MAP_SIZE);
+        assert_eq!(tiles.len(), 4);
+        assert!(tiles.contains(&start) && tiles.contains(&goal));
+    }
+
+    #[test]
+    fn higher_turn_cost_gives_straighter_shapes() {
+        let start = TilePos { x: 3, y: 3 };
+        let reached_tiles = |turn_cost| -> HashSet<TilePos> {
+            reachable_with_facing(
+                start,
+                HexDirection::Zero,
+                3,
+                1,
+                turn_cost,
+                |_| true,
+                HexCoordSystem::Row,
+                &MAP_SIZE,
+            )
+            .into_keys()
+            .map(|(tile_pos, _)| tile_pos)
+            .collect()
+        };
+
+        let free_turns = reached_tiles(0);
+        let expected: HashSet<TilePos> = AxialPos::from(&start)
+            .range(3)
+            .into_iter()
+            .filter_map(|axial_pos| axial_pos.as_tile_pos(&MAP_SIZE))
+            .collect();
+        assert_eq!(free_turns, expected);
+
+        let cheap_turns = reached_tiles(1);
+        assert!(cheap_turns.len() < free_turns.len());
+        assert!(cheap_turns.is_subset(&free_turns));
+
+        let no_turns = reached_tiles(4);
+        let straight: HashSet<TilePos> = (3..=6).map(|x| TilePos { x, y: 3 }).collect();
+        assert_eq!(no_turns, straight);
+    }
+
+    #[test]
+    fn path_cost_equals_the_astar_cost() {
+        let cost = |_: TilePos, to: TilePos| {
+            if to.x == 3 && to.y < 6 {
+                None
+            } else {
+                Some(1 + (to.x * 7 + to.y * 3) % 5)
+            }
+        };
+        let start = TilePos { x: 0, y: 0 };
+        for y in 0..MAP_SIZE.y {
+            for x in 0..MAP_SIZE.x {
+                let goal = TilePos { x, y };
+                let found = astar(start, goal, cost, 1, HexCoordSystem::RowOdd, &MAP_SIZE);
+                let total = path_cost(start, goal, cost, HexCoordSystem::RowOdd, &MAP_SIZE);
+                assert_eq!(found.as_ref().map(|(_, total)| *total), total);
+                if let Some((path, total)) = found {
+                    let summed: u32 = path
+                        .windows(2)
+                        .map(|step| cost(step[0], step[1]).unwrap())
+                        .sum();
+                    assert_eq!(summed, total);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn multi_waypoint_path_concatenates_the_legs() {
+        let cost = |_: TilePos, to: TilePos| if to.x == 3 && to.y > 0 { None } else { Some(1) };
+        let waypoints = [
+            TilePos { x: 1, y: 4 },
+            TilePos { x: 6, y: 5 },
+            TilePos { x: 5, y: 1 },
+        ];
+
+        let mut expected = Vec::new();
+        for leg in waypoints.windows(2) {
+            let (leg_path, _) =
+                astar(leg[0], leg[1], cost, 1, HexCoordSystem::Row, &MAP_SIZE).unwrap();
+            if !expected.is_empty() {
+                assert_eq!(expected.pop(), leg_path.first().copied());
+            }
+            expected.extend(leg_path);
+        }
+        assert_eq!(
+            multi_waypoint_path(&waypoints, cost, 1, HexCoordSystem::Row, &MAP_SIZE),
+            Some(expected)
+        );
+
+        assert_eq!(
+            multi_waypoint_path(&waypoints[..1], cost, 1, HexCoordSystem::Row, &MAP_SIZE),
+            Some(vec![waypoints[0]])
+        );
+        let walled_in = TilePos { x: 3, y: 4 };
+        assert_eq!(
+            multi_waypoint_path(
+                &[waypoints[0], walled_in],
+                cost,
+                1,
+                HexCoordSystem::Row,
+                &MAP_SIZE
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn reachable_time_shrinks_with_the_budget() {
+        let start = TilePos { x: 4, y: 4 };
+        let within = |radius: u32| -> HashSet<TilePos> {
+            AxialPos::from(&start)
+                .range(radius)
+                .into_iter()
+                .filter_map(|axial_pos| axial_pos.as_tile_pos(&MAP_SIZE))
+                .collect()
+        };
+        let reached = |budget: f32, time: f32| {
+            reachable_time(
+                start,
+                budget,
+                |_| Some(time),
+                HexCoordSystem::Row,
+                &MAP_SIZE,
+            )
+        };
+
+        let full = reached(4.0, 1.0);
+        let half = reached(2.0, 1.0);
+        assert_eq!(
+            full.keys().copied().collect::<HashSet<TilePos>>(),
+            within(4)
+        );
+        assert_eq!(
+            half.keys().copied().collect::<HashSet<TilePos>>(),
+            within(2)
+        );
+        for (tile_pos, time) in half.iter() {
+            assert_eq!(full[tile_pos], *time);
+            let distance = AxialPos::from(tile_pos).distance_from(&AxialPos::from(&start));
+            assert_eq!(*time, distance as f32);
+        }
+
+        // Halving the budget is the same as doubling the time needed for each step.
+        let slow = reached(4.0, 2.0);
+        assert_eq!(
+            slow.keys().copied().collect::<HashSet<TilePos>>(),
+            half.keys().copied().collect::<HashSet<TilePos>>()
+        );
+        assert_eq!(reached(0.0, 1.0).len(), 1);
+    }
+
+    #[test]
+    fn astar_with_zoc_stops_on_entering_a_zone_of_control() {
+        let enemy = AxialPos { q: 4, r: 3 };
+        let zone: HashSet<TilePos> = enemy
+            .zone_of_control(HexCoordSystem::Row, &MAP_SIZE)
+            .into_iter()
+            .collect();
+        let cost = |_: TilePos, to: TilePos| {
+            if to == enemy.as_tile_pos(&MAP_SIZE).unwrap() {
+                None
+            } else {
+                Some(1)
+            }
+        };
+        let zoc = |tile_pos: TilePos| zone.contains(&tile_pos);
+        let path_through = |start, goal| {
+            astar_with_zoc(start, goal, cost, 1, zoc, HexCoordSystem::Row, &MAP_SIZE).unwrap()
+        };
+
+        // Passing by the enemy means going around its zone of control.
+        let (start, goal) = (TilePos { x: 1, y: 3 }, TilePos { x: 7, y: 3 });
+        let around = path_through(start, goal);
+        assert!(around.iter().all(|tile_pos| !zoc(*tile_pos)));
+        let (direct, _) = astar(start, goal, cost, 1, HexCoordSystem::Row, &MAP_SIZE).unwrap();
+        assert!(around.len() > direct.len());
+
+        // A zone of control can be entered as the last step only.
+        let goal = TilePos { x: 5, y: 3 };
+        let into = path_through(start, goal);
+        assert_eq!(into.last(), Some(&goal));
+        assert!(into[..into.len() - 1]
+            .iter()
+            .all(|tile_pos| !zoc(*tile_pos)));
+
+        // A unit starting in a zone of control may leave it.
+        let start = TilePos { x: 3, y: 3 };
+        assert!(zoc(start));
+        let out = path_through(start, TilePos { x: 0, y: 3 });
+        assert_eq!(out.len(), 4);
+    }
+
+    #[test]
+    fn danger_weighted_cost_routes_around_high_penalties() {
+        let danger_zone = TilePos { x: 4, y: 3 };
+        let danger = |tile_pos: TilePos| if tile_pos == danger_zone { 1.0 } else { 0.0 };
+        let base_cost = |_: TilePos, to: TilePos| {
+            if to.x == 6 && to.y == 0 {
+                None
+            } else {
+                Some(1)
+            }
+        };
+        let (start, goal) = (TilePos { x: 1, y: 3 }, TilePos { x: 7, y: 3 });
+        let route = |penalty_scale: f32| {
+            let cost = danger_weighted_cost(base_cost, danger, penalty_scale);
+            astar(start, goal, cost, 1, HexCoordSystem::Row, &MAP_SIZE).unwrap()
+        };
+
+        let (through, through_cost) = route(0.4);
+        assert!(through.contains(&danger_zone));
+        assert_eq!(through_cost, 6);
+
+        let (around, around_cost) = route(10.0);
+        assert!(!around.contains(&danger_zone));
+        assert_eq!(around_cost, 7);
+
+        let cost = danger_weighted_cost(base_cost, danger, 10.0);
+        assert_eq!(cost(start, TilePos { x: 6, y: 0 }), None);
+        assert_eq!(cost(start, danger_zone), Some(11));
+    }
+
+    #[test]
+    fn danger_weighted_cost_saturates_infinite_danger() {
+        let danger_zone = TilePos { x: 4, y: 3 };
+        let danger = |tile_pos: TilePos| {
+            if tile_pos == danger_zone {
+                f32::INFINITY
+            } else {
+                0.0
+            }
+        };
+        let cost = danger_weighted_cost(|_, _| Some(1), danger, 1.0);
+        assert_eq!(cost(TilePos { x: 3, y: 3 }, danger_zone), Some(u32::MAX));
+
+        let (start, goal) = (TilePos { x: 1, y: 3 }, TilePos { x: 7, y: 3 });
+        let (around, around_cost) =
+            astar(start, goal, &cost, 1, HexCoordSystem::Row, &MAP_SIZE).unwrap();
+        assert!(!around.contains(&danger_zone));
+        assert_eq!(around_cost, 7);
+
+        let into = path_cost(start, danger_zone, &cost, HexCoordSystem::Row, &MAP_SIZE);
+        assert_eq!(into, Some(u32::MAX));
+    }
+}
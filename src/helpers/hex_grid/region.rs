@@ -0,0 +1,1251 @@
+use crate::helpers::hex_grid::axial::{AxialPos, FractionalAxialPos};
+use crate::helpers::hex_grid::neighbors::HEX_OFFSETS;
+use crate::map::HexCoordSystem;
+use crate::tiles::TilePos;
+use crate::{TilemapGridSize, TilemapSize};
+use bevy::math::{DVec2, Vec2};
+use bevy::utils::{HashMap, HashSet};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::cmp::{Ordering, Reverse};
+use std::collections::VecDeque;
+
+/// Returns a hexagonal region, given as a `(center, radius)` pair, which contains every one of the
+/// given `tiles`.
+///
+/// The center is the centroid of `tiles`, rounded to the nearest hex, and the radius is the
+/// largest distance from that center to any of `tiles`. This is an approximation: the returned
+/// region always encloses `tiles`, but it is not guaranteed to be the smallest one that does.
+///
+/// Returns `None` if `tiles` is empty.
+pub fn bounding_hex(tiles: &[AxialPos]) -> Option<(AxialPos, u32)> {
+    if tiles.is_empty() {
+        return None;
+    }
+
+    let (sum_q, sum_r) = tiles.iter().fold((0.0, 0.0), |(q, r), tile| {
+        (q + tile.q as f32, r + tile.r as f32)
+    });
+    let n = tiles.len() as f32;
+    let center = FractionalAxialPos {
+        q: sum_q / n,
+        r: sum_r / n,
+    }
+    .round();
+
+    let radius = tiles
+        .iter()
+        .map(|tile| tile.distance_from(&center))
+        .max()
+        .unwrap_or(0);
+
+    Some((center, radius as u32))
+}
+
+/// Translates each offset in `pattern` by `at`, and returns the resulting tile positions which lie
+/// within `map_size`. This is useful for placing multi-tile structures.
+///
+/// If `require_all_in_bounds` is `true`, then an empty `Vec` is returned unless every tile of
+/// the stamped pattern lies within `map_size`.
+pub fn stamp(
+    pattern: &[AxialPos],
+    at: AxialPos,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+    require_all_in_bounds: bool,
+) -> Vec<TilePos> {
+    let tiles: Vec<TilePos> = pattern
+        .iter()
+        .filter_map(|offset| (at + *offset).as_tile_pos_given_coord_system(map_size, hex_coord_sys))
+        .collect();
+
+    if require_all_in_bounds && tiles.len() != pattern.len() {
+        Vec::new()
+    } else {
+        tiles
+    }
+}
+
+/// Returns the weight-averaged axial position of the given `(tile, weight)` pairs.
+///
+/// The result is not rounded; use [`FractionalAxialPos::round`] to obtain the hex containing it.
+///
+/// Returns `None` if the weights sum to zero.
+pub fn weighted_centroid(tiles: &[(AxialPos, f32)]) -> Option<FractionalAxialPos> {
+    let (mut sum_q, mut sum_r, mut total_weight) = (0.0, 0.0, 0.0);
+    for (tile, weight) in tiles.iter() {
+        sum_q += weight * tile.q as f32;
+        sum_r += weight * tile.r as f32;
+        total_weight += weight;
+    }
+
+    if total_weight == 0.0 {
+        None
+    } else {
+        Some(FractionalAxialPos {
+            q: sum_q / total_weight,
+            r: sum_r / total_weight,
+        })
+    }
+}
+
+/// Returns every unordered pair of adjacent hexes within `tiles`. Each pair is returned exactly
+/// once.
+pub fn adjacent_pairs(tiles: &HashSet<AxialPos>) -> Vec<(AxialPos, AxialPos)> {
+    // Only half of the directions are checked, so that each pair is only found from one of its
+    // two members.
+    tiles
+        .iter()
+        .flat_map(|tile| {
+            HEX_OFFSETS[..3]
+                .iter()
+                .map(move |offset| (*tile, *tile + *offset))
+        })
+        .filter(|(_, neighbor)| tiles.contains(neighbor))
+        .collect()
+}
+
+/// Returns every pair `(a, b)` of adjacent hexes such that `a` lies in `region_a` and `b` lies in
+/// `region_b`, i.e. the frontier between the two regions.
+///
+/// A tile may appear in several pairs, once for each of its neighbors across the frontier. Tiles
+/// belonging to both regions are treated as members of each.
+pub fn border_between(
+    region_a: &HashSet<AxialPos>,
+    region_b: &HashSet<AxialPos>,
+) -> Vec<(AxialPos, AxialPos)> {
+    region_a
+        .iter()
+        .flat_map(|tile| {
+            tile.neighbors()
+                .into_iter()
+                .map(move |neighbor| (*tile, neighbor))
+        })
+        .filter(|(_, neighbor)| region_b.contains(neighbor))
+        .collect()
+}
+
+/// Returns the vertices of the convex hull of the world space centers of `tiles`, in
+/// counter-clockwise order.
+///
+/// The hull is computed using Andrew's monotone chain algorithm. Duplicate tiles are ignored, and
+/// centers lying on an edge of the hull are not included as vertices. If there are fewer than
+/// three distinct centers, they are returned as is.
+pub fn convex_hull(
+    tiles: &[AxialPos],
+    grid_size: &TilemapGridSize,
+    hex_coord_sys: HexCoordSystem,
+) -> Vec<Vec2> {
+    let mut tiles = tiles.to_vec();
+    tiles.sort();
+    tiles.dedup();
+
+    let mut points: Vec<Vec2> = tiles
+        .iter()
+        .map(|tile| tile.center_in_world_given_coord_system(grid_size, hex_coord_sys))
+        .collect();
+    points.sort_by(|a, b| {
+        a.x.partial_cmp(&b.x)
+            .unwrap()
+            .then(a.y.partial_cmp(&b.y).unwrap())
+    });
+
+    if points.len() < 3 {
+        return points;
+    }
+
+    let cross = |o: Vec2, a: Vec2, b: Vec2| (a - o).perp_dot(b - o);
+
+    let mut hull: Vec<Vec2> = Vec::with_capacity(2 * points.len());
+    // Lower hull.
+    for point in points.iter() {
+        while hull.len() >= 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], *point) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(*point);
+    }
+    // Upper hull.
+    let lower_len = hull.len() + 1;
+    for point in points.iter().rev().skip(1) {
+        while hull.len() >= lower_len
+            && cross(hull[hull.len() - 2], hull[hull.len() - 1], *point) <= 0.0
+        {
+            hull.pop();
+        }
+        hull.push(*point);
+    }
+    // The last point is the same as the first one.
+    hull.pop();
+
+    hull
+}
+
+/// Returns the winding of the polygon formed by the world space centers of `loop_tiles`, taken
+/// in order, as the sign of its signed area.
+///
+/// [`Ordering::Greater`] means that the loop runs counter-clockwise, and [`Ordering::Less`] that it
+/// runs clockwise. [`Ordering::Equal`] is returned for degenerate loops with no area, such as
+/// those with fewer than three tiles. The loop is closed automatically.
+pub fn loop_winding(
+    loop_tiles: &[AxialPos],
+    grid_size: &TilemapGridSize,
+    hex_coord_sys: HexCoordSystem,
+) -> Ordering {
+    let points: Vec<Vec2> = loop_tiles
+        .iter()
+        .map(|tile| tile.center_in_world_given_coord_system(grid_size, hex_coord_sys))
+        .collect();
+    // Twice the signed area, by the shoelace formula.
+    let area: f32 = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum();
+    area.partial_cmp(&0.0).unwrap_or(Ordering::Equal)
+}
+
+/// Returns a Delaunay triangulation of the world space centers of `points`, as triples of indices
+/// into `points`. Each triangle's vertices are given in counter-clockwise order.
+///
+/// The points are first swept in sorted order, connecting each one to the edges of the convex hull
+/// of the previous ones, which triangulates the whole convex hull. Edges are then flipped until
+/// every edge is locally Delaunay (Lawson's algorithm). Cocircular points, which are common on a
+/// hex grid, are triangulated arbitrarily.
+///
+/// Duplicate points are ignored, so only the index of the first occurrence of a point is ever
+/// returned. If all of the points are collinear, no triangles are returned.
+pub fn triangulate(
+    points: &[AxialPos],
+    grid_size: &TilemapGridSize,
+    hex_coord_sys: HexCoordSystem,
+) -> Vec<[usize; 3]> {
+    let vertices: Vec<DVec2> = points
+        .iter()
+        .map(|point| {
+            point
+                .center_in_world_given_coord_system(grid_size, hex_coord_sys)
+                .as_dvec2()
+        })
+        .collect();
+
+    // Positive if `c` lies to the left of the line from `a` to `b`, and `0.0` if the three are
+    // collinear, up to rounding errors.
+    let orientation = |a: usize, b: usize, c: usize| {
+        let (u, v) = (vertices[b] - vertices[a], vertices[c] - vertices[a]);
+        let det = u.perp_dot(v);
+        if det.abs() <= 1e-6 * u.length() * v.length() {
+            0.0
+        } else {
+            det
+        }
+    };
+    // Whether `d` lies strictly inside the circumcircle of the counter-clockwise triangle `abc`.
+    let in_circumcircle = |[a, b, c]: [usize; 3], d: usize| {
+        let [a, b, c] = [a, b, c].map(|v| vertices[v] - vertices[d]);
+        let terms = [
+            a.length_squared() * b.perp_dot(c),
+            -b.length_squared() * a.perp_dot(c),
+            c.length_squared() * a.perp_dot(b),
+        ];
+        let det: f64 = terms.iter().sum();
+        let scale: f64 = terms.iter().map(|term| term.abs()).sum();
+        det > 1e-6 * scale
+    };
+
+    let mut seen: HashSet<AxialPos> = HashSet::default();
+    let mut order: Vec<usize> = (0..points.len())
+        .filter(|i| seen.insert(points[*i]))
+        .collect();
+    order.sort_by(|a, b| {
+        let (a, b) = (vertices[*a], vertices[*b]);
+        a.x.partial_cmp(&b.x)
+            .unwrap_or(Ordering::Equal)
+            .then(a.y.partial_cmp(&b.y).unwrap_or(Ordering::Equal))
+    });
+    if order.len() < 3 {
+        return Vec::new();
+    }
+
+    // The leading points all lie on a line; fan them out to the first point off of that line.
+    let first_apex =
+        match (2..order.len()).find(|k| orientation(order[0], order[1], order[*k]) != 0.0) {
+            Some(first_apex) => first_apex,
+            None => return Vec::new(),
+        };
+    let apex = order[first_apex];
+    let mut line = order[..first_apex].to_vec();
+    if orientation(order[0], order[1], apex) < 0.0 {
+        line.reverse();
+    }
+    let mut triangles: Vec<[usize; 3]> = line
+        .windows(2)
+        .map(|pair| [pair[0], pair[1], apex])
+        .collect();
+    let mut hull = line;
+    hull.push(apex);
+
+    // Each remaining point lies outside of the current hull, and sees a contiguous run of its
+    // edges, which are replaced by the two edges joining the ends of that run to the point.
+    for point in order[(first_apex + 1)..].iter().copied() {
+        let m = hull.len();
+        let visible: Vec<bool> = (0..m)
+            .map(|i| orientation(hull[i], hull[(i + 1) % m], point) < 0.0)
+            .collect();
+        let run_start = match (0..m).find(|i| visible[*i] && !visible[(*i + m - 1) % m]) {
+            Some(run_start) => run_start,
+            None => continue,
+        };
+        let run_length = visible.iter().filter(|visible| **visible).count();
+        for i in run_start..(run_start + run_length) {
+            triangles.push([hull[(i + 1) % m], hull[i % m], point]);
+        }
+        let run_end = run_start + run_length;
+        let mut next_hull: Vec<usize> = (run_end..=(run_start + m)).map(|i| hull[i % m]).collect();
+        next_hull.push(point);
+        hull = next_hull;
+    }
+
+    // Flip edges which are not locally Delaunay, until none are left.
+    let mut edges: HashMap<(usize, usize), usize> = HashMap::default();
+    for (t, [a, b, c]) in triangles.iter().enumerate() {
+        for edge in [(*a, *b), (*b, *c), (*c, *a)] {
+            edges.insert(edge, t);
+        }
+    }
+    let mut pending: Vec<(usize, usize)> = edges.keys().copied().collect();
+    while let Some((a, b)) = pending.pop() {
+        let (t, u) = match (edges.get(&(a, b)), edges.get(&(b, a))) {
+            (Some(t), Some(u)) => (*t, *u),
+            _ => continue,
+        };
+        let opposite = |triangle: [usize; 3]| {
+            triangle
+                .into_iter()
+                .find(|vertex| *vertex != a && *vertex != b)
+                .unwrap()
+        };
+        let (c, d) = (opposite(triangles[t]), opposite(triangles[u]));
+        if !in_circumcircle([a, b, c], d) {
+            continue;
+        }
+
+        edges.remove(&(a, b));
+        edges.remove(&(b, a));
+        triangles[t] = [a, d, c];
+        triangles[u] = [d, b, c];
+        for (edge, triangle) in [
+            ((a, d), t),
+            ((d, c), t),
+            ((c, a), t),
+            ((d, b), u),
+            ((b, c), u),
+            ((c, d), u),
+        ] {
+            edges.insert(edge, triangle);
+        }
+        pending.extend([(a, d), (d, b), (b, c), (c, a)]);
+    }
+
+    triangles
+}
+
+/// Labels the tiles reachable from `seeds` through tiles which are `passable` with the label of
+/// the nearest seed, using a level-by-level multi-source breadth first search. Ties are broken in
+/// favor of the lowest label.
+fn grow_regions(
+    seeds: &[(AxialPos, u32)],
+    passable: impl Fn(TilePos) -> bool,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashMap<TilePos, u32> {
+    let mut labels: HashMap<TilePos, u32> = HashMap::default();
+    for (seed, label) in seeds.iter() {
+        if let Some(tile_pos) = seed.as_tile_pos_given_coord_system(map_size, hex_coord_sys) {
+            let entry = labels.entry(tile_pos).or_insert(*label);
+            *entry = (*entry).min(*label);
+        }
+    }
+
+    let mut frontier: Vec<TilePos> = labels.keys().copied().collect();
+    while !frontier.is_empty() {
+        let mut next_labels: HashMap<TilePos, u32> = HashMap::default();
+        for tile_pos in frontier.iter() {
+            let label = labels[tile_pos];
+            let axial_pos = AxialPos::from_tile_pos_given_coord_system(tile_pos, hex_coord_sys);
+            for neighbor in axial_pos.neighbors() {
+                let neighbor =
+                    match neighbor.as_tile_pos_given_coord_system(map_size, hex_coord_sys) {
+                        Some(neighbor) => neighbor,
+                        None => continue,
+                    };
+                if labels.contains_key(&neighbor) || !passable(neighbor) {
+                    continue;
+                }
+                let entry = next_labels.entry(neighbor).or_insert(label);
+                *entry = (*entry).min(label);
+            }
+        }
+
+        frontier = next_labels.keys().copied().collect();
+        labels.extend(next_labels);
+    }
+
+    labels
+}
+
+/// Labels each tile of the map with the label of the seed nearest to it, by hex distance. Ties
+/// are broken in favor of the lowest label.
+///
+/// Seeds lying outside of `map_size` are ignored.
+pub fn voronoi(
+    seeds: &[(AxialPos, u32)],
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashMap<TilePos, u32> {
+    grow_regions(seeds, |_| true, hex_coord_sys, map_size)
+}
+
+/// Labels each tile of the map with the label of the seed nearest to it, where distance is
+/// measured along paths which only pass through `passable` tiles. Ties are broken in favor of the
+/// lowest label.
+///
+/// Tiles which cannot be reached from any seed are left unlabeled. Seed tiles are always labeled,
+/// even if they are not `passable`. Seeds lying outside of `map_size` are ignored.
+pub fn voronoi_bounded(
+    seeds: &[(AxialPos, u32)],
+    passable: impl Fn(TilePos) -> bool,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashMap<TilePos, u32> {
+    grow_regions(seeds, passable, hex_coord_sys, map_size)
+}
+
+/// Returns every pair of adjacent tiles with different labels in `labels`, such as those produced
+/// by [`voronoi`], i.e. the borders between labeled regions.
+///
+/// Each pair is returned exactly once, ordered by the row-major order of its first tile. Tiles
+/// which are unlabeled, or lie outside of `map_size`, are ignored.
+pub fn voronoi_borders(
+    labels: &HashMap<TilePos, u32>,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> Vec<(TilePos, TilePos)> {
+    let mut borders = Vec::new();
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            let tile_pos = TilePos { x, y };
+            let label = match labels.get(&tile_pos) {
+                Some(label) => label,
+                None => continue,
+            };
+            // Only half of the directions are checked, so that each pair is only found from one
+            // of its two members.
+            let axial_pos = AxialPos::from_tile_pos_given_coord_system(&tile_pos, hex_coord_sys);
+            for offset in HEX_OFFSETS[..3].iter() {
+                let neighbor = match (axial_pos + *offset)
+                    .as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+                {
+                    Some(neighbor) => neighbor,
+                    None => continue,
+                };
+                match labels.get(&neighbor) {
+                    Some(neighbor_label) if neighbor_label != label => {
+                        borders.push((tile_pos, neighbor));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    borders
+}
+
+/// Returns a random selection of tiles of the map, such that every two selected tiles are at
+/// least `min_distance` apart, and no further tile could be added without breaking this rule.
+///
+/// Every tile of the map is tried once, in a random order, and accepted if it is far enough from
+/// every tile accepted so far. Accepted tiles are bucketed into cells of `min_distance` by
+/// `min_distance` axial coordinates, so that only nearby buckets need to be checked. Tiles are
+/// returned in the order they were accepted.
+pub fn poisson_disk(
+    min_distance: u32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+    rng: &mut impl Rng,
+) -> Vec<TilePos> {
+    let mut candidates: Vec<TilePos> = Vec::with_capacity(map_size.count());
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            candidates.push(TilePos { x, y });
+        }
+    }
+    candidates.shuffle(rng);
+
+    // Two tiles less than `min_distance` apart differ by less than `cell_size` in both `q` and
+    // `r`, so they lie in the same or in adjacent cells.
+    let cell_size = min_distance.max(1) as i32;
+    let cell_of = |axial_pos: AxialPos| {
+        (
+            axial_pos.q.div_euclid(cell_size),
+            axial_pos.r.div_euclid(cell_size),
+        )
+    };
+
+    let mut cells: HashMap<(i32, i32), Vec<AxialPos>> = HashMap::default();
+    let mut accepted = Vec::new();
+    for tile_pos in candidates {
+        let axial_pos = AxialPos::from_tile_pos_given_coord_system(&tile_pos, hex_coord_sys);
+        let (cell_q, cell_r) = cell_of(axial_pos);
+        let too_close = (-1..=1).any(|dq| {
+            (-1..=1).any(|dr| {
+                cells
+                    .get(&(cell_q + dq, cell_r + dr))
+                    .into_iter()
+                    .flatten()
+                    .any(|other| other.distance_from(&axial_pos) < min_distance as i32)
+            })
+        });
+        if !too_close {
+            cells.entry((cell_q, cell_r)).or_default().push(axial_pos);
+            accepted.push(tile_pos);
+        }
+    }
+    accepted
+}
+
+/// Returns the tiles of `tiles` which can be reached from `start` by stepping between adjacent
+/// members of `tiles`.
+fn flood(start: AxialPos, tiles: &HashSet<AxialPos>) -> HashSet<AxialPos> {
+    let mut reached = HashSet::default();
+    reached.insert(start);
+    let mut stack = vec![start];
+    while let Some(current) = stack.pop() {
+        for neighbor in current.neighbors() {
+            if tiles.contains(&neighbor) && reached.insert(neighbor) {
+                stack.push(neighbor);
+            }
+        }
+    }
+    reached
+}
+
+/// Returns `true` if every tile in `tiles` can be reached from every other one by stepping between
+/// adjacent members of `tiles`.
+///
+/// An empty set is considered to be connected.
+pub fn is_connected(tiles: &HashSet<AxialPos>) -> bool {
+    match tiles.iter().next() {
+        Some(start) => flood(*start, tiles).len() == tiles.len(),
+        None => true,
+    }
+}
+
+/// Returns the connected components of `tiles`: the maximal subsets of `tiles` within which every
+/// tile can be reached from every other one by stepping between adjacent members.
+///
+/// Components are returned in no particular order.
+pub fn connected_components(tiles: &HashSet<AxialPos>) -> Vec<HashSet<AxialPos>> {
+    let mut components: Vec<HashSet<AxialPos>> = Vec::new();
+    let mut assigned: HashSet<AxialPos> = HashSet::default();
+    for tile in tiles.iter() {
+        if !assigned.contains(tile) {
+            let component = flood(*tile, tiles);
+            assigned.extend(component.iter().copied());
+            components.push(component);
+        }
+    }
+    components
+}
+
+/// Returns the number of steps needed to reach each tile of `tiles` from `start`, stepping only
+/// between adjacent members of `tiles`. Unreachable tiles are omitted.
+fn bfs_distances(start: AxialPos, tiles: &HashSet<AxialPos>) -> HashMap<AxialPos, u32> {
+    let mut distances = HashMap::default();
+    distances.insert(start, 0);
+    let mut queue = VecDeque::from([start]);
+    while let Some(current) = queue.pop_front() {
+        let distance = distances[&current];
+        for neighbor in current.neighbors() {
+            if tiles.contains(&neighbor) && !distances.contains_key(&neighbor) {
+                distances.insert(neighbor, distance + 1);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    distances
+}
+
+/// Returns the diameter of `tiles`: the largest number of steps on the shortest path between any
+/// two of its members, stepping only between adjacent members.
+///
+/// Since this performs a breadth first search from every tile, it is intended for small regions.
+/// If `tiles` is not connected, the largest diameter among its connected components is returned.
+/// Returns `0` for an empty set.
+pub fn region_diameter(tiles: &HashSet<AxialPos>) -> u32 {
+    tiles
+        .iter()
+        .filter_map(|tile| bfs_distances(*tile, tiles).into_values().max())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Returns the tile of `tiles` which minimizes the sum of shortest path distances to every other
+/// member, stepping only between adjacent members. Ties are broken in favor of the smallest
+/// [`AxialPos`].
+///
+/// Since this performs a breadth first search from every tile, it is intended for small regions.
+/// If `tiles` is not connected, tiles which can reach more members are preferred. Returns `None`
+/// for an empty set.
+pub fn region_center(tiles: &HashSet<AxialPos>) -> Option<AxialPos> {
+    tiles
+        .iter()
+        .map(|tile| {
+            let distances = bfs_distances(*tile, tiles);
+            let total: u32 = distances.values().sum();
+            (Reverse(distances.len()), total, *tile)
+        })
+        .min()
+        .map(|(_, _, tile)| tile)
+}
+
+/// Returns the tiles of the map which are enclosed by `loop_tiles`: those which are not members of
+/// `loop_tiles`, and cannot be reached from the border of the map without crossing one of them.
+///
+/// Returns `None` if `loop_tiles` does not enclose any tile. Members of `loop_tiles` lying outside
+/// of `map_size` are ignored.
+pub fn enclosed_area(
+    loop_tiles: &HashSet<AxialPos>,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> Option<HashSet<AxialPos>> {
+    let mut open: HashSet<AxialPos> = HashSet::default();
+    let mut border: Vec<AxialPos> = Vec::new();
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            let axial_pos =
+                AxialPos::from_tile_pos_given_coord_system(&TilePos { x, y }, hex_coord_sys);
+            if loop_tiles.contains(&axial_pos) {
+                continue;
+            }
+            open.insert(axial_pos);
+            if x == 0 || y == 0 || x == map_size.x - 1 || y == map_size.y - 1 {
+                border.push(axial_pos);
+            }
+        }
+    }
+
+    let mut outside: HashSet<AxialPos> = HashSet::default();
+    for tile in border {
+        if !outside.contains(&tile) {
+            outside.extend(flood(tile, &open));
+        }
+    }
+
+    let interior: HashSet<AxialPos> = open.difference(&outside).copied().collect();
+    if interior.is_empty() {
+        None
+    } else {
+        Some(interior)
+    }
+}
+
+/// Runs Tarjan's low-link depth first search over `tiles`, stepping only between adjacent
+/// members, and returns the discovery time of each tile along with the edges of the search
+/// forest.
+///
+/// Each forest edge is given as `(parent, child, low)`, where `low` is the lowest discovery time
+/// reachable from the subtree of `child` using at most one back edge.
+fn low_link(tiles: &HashSet<AxialPos>) -> (HashMap<AxialPos, u32>, Vec<(AxialPos, AxialPos, u32)>) {
+    let mut discovery: HashMap<AxialPos, u32> = HashMap::default();
+    let mut low: HashMap<AxialPos, u32> = HashMap::default();
+    let mut tree_edges = Vec::new();
+    let mut time = 0;
+
+    for root in tiles.iter() {
+        if discovery.contains_key(root) {
+            continue;
+        }
+        discovery.insert(*root, time);
+        low.insert(*root, time);
+        time += 1;
+
+        // Each entry holds a tile, its parent in the search tree, and the index of the next
+        // neighbor to visit.
+        let mut stack: Vec<(AxialPos, Option<AxialPos>, usize)> = vec![(*root, None, 0)];
+        while let Some(&(tile, parent, index)) = stack.last() {
+            if index < HEX_OFFSETS.len() {
+                stack.last_mut().unwrap().2 += 1;
+                let neighbor = tile + HEX_OFFSETS[index];
+                if !tiles.contains(&neighbor) || Some(neighbor) == parent {
+                    continue;
+                }
+                if let Some(neighbor_discovery) = discovery.get(&neighbor) {
+                    let tile_low = low.get_mut(&tile).unwrap();
+                    *tile_low = (*tile_low).min(*neighbor_discovery);
+                } else {
+                    discovery.insert(neighbor, time);
+                    low.insert(neighbor, time);
+                    time += 1;
+                    stack.push((neighbor, Some(tile), 0));
+                }
+            } else {
+                stack.pop();
+                if let Some(parent) = parent {
+                    let tile_low = low[&tile];
+                    let parent_low = low.get_mut(&parent).unwrap();
+                    *parent_low = (*parent_low).min(tile_low);
+                    tree_edges.push((parent, tile, tile_low));
+                }
+            }
+        }
+    }
+
+    (discovery, tree_edges)
+}
+
+/// Returns the articulation points of `tiles`: the members whose removal would split their
+/// connected component into several pieces, stepping only between adjacent members.
+///
+/// These are found with Tarjan's low-link algorithm, using an iterative depth first search.
+pub fn articulation_points(tiles: &HashSet<AxialPos>) -> HashSet<AxialPos> {
+    let (discovery, tree_edges) = low_link(tiles);
+    let children: HashSet<AxialPos> = tree_edges.iter().map(|(_, child, _)| *child).collect();
+
+    let mut points = HashSet::default();
+    let mut root_children: HashMap<AxialPos, u32> = HashMap::default();
+    for (parent, _, child_low) in tree_edges.iter() {
+        if !children.contains(parent) {
+            // The root of a search tree is an articulation point if it has several children.
+            *root_children.entry(*parent).or_insert(0) += 1;
+        } else if *child_low >= discovery[parent] {
+            points.insert(*parent);
+        }
+    }
+    points.extend(
+        root_children
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(root, _)| root),
+    );
+
+    points
+}
+
+/// Returns the bridges of `tiles`: the pairs of adjacent members such that removing the edge
+/// between them would split their connected component into two pieces.
+///
+/// These are found with Tarjan's low-link algorithm, using an iterative depth first search. Each
+/// bridge is returned once, as a `(parent, child)` pair of the search tree.
+pub fn bridges(tiles: &HashSet<AxialPos>) -> Vec<(AxialPos, AxialPos)> {
+    let (discovery, tree_edges) = low_link(tiles);
+    tree_edges
+        .into_iter()
+        .filter(|(parent, _, child_low)| *child_low > discovery[parent])
+        .map(|(parent, child, _)| (parent, child))
+        .collect()
+}
+
+/// Maps each tile of `tiles` to the number of `sample_pairs` for which it lies on a shortest path
+/// from the source to the target, stepping only between adjacent members of `tiles`. Tiles with
+/// high counts are natural thoroughfares.
+///
+/// Both endpoints of a pair count as lying on its shortest paths. Pairs whose target cannot be
+/// reached from their source are skipped, and tiles which lie on no shortest path are omitted.
+pub fn path_betweenness(
+    tiles: &HashSet<AxialPos>,
+    sample_pairs: &[(AxialPos, AxialPos)],
+) -> HashMap<AxialPos, u32> {
+    let mut counts: HashMap<AxialPos, u32> = HashMap::default();
+    for (source, target) in sample_pairs.iter() {
+        if !tiles.contains(source) || !tiles.contains(target) {
+            continue;
+        }
+        let from_source = bfs_distances(*source, tiles);
+        let length = match from_source.get(target) {
+            Some(length) => *length,
+            None => continue,
+        };
+        let from_target = bfs_distances(*target, tiles);
+        for (tile, distance) in from_source.iter() {
+            let on_shortest_path = match from_target.get(tile) {
+                Some(to_target) => distance + to_target == length,
+                None => false,
+            };
+            if on_shortest_path {
+                *counts.entry(*tile).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const GRID_SIZE: TilemapGridSize = TilemapGridSize { x: 16.0, y: 16.0 };
+
+    fn polygon_area(polygon: &[Vec2]) -> f32 {
+        polygon
+            .iter()
+            .zip(polygon.iter().cycle().skip(1))
+            .map(|(a, b)| a.perp_dot(*b))
+            .sum::<f32>()
+            / 2.0
+    }
+
+    fn circumcenter(a: Vec2, b: Vec2, c: Vec2) -> Vec2 {
+        let (b, c) = (b - a, c - a);
+        let d = 2.0 * b.perp_dot(c);
+        a + Vec2::new(
+            c.y * b.length_squared() - b.y * c.length_squared(),
+            b.x * c.length_squared() - c.x * b.length_squared(),
+        ) / d
+    }
+
+    fn triangulated_area(points: &[AxialPos], triangles: &[[usize; 3]]) -> f32 {
+        triangles
+            .iter()
+            .map(|triangle| {
+                let corners = triangle.map(|i| {
+                    points[i].center_in_world_given_coord_system(&GRID_SIZE, HexCoordSystem::Row)
+                });
+                polygon_area(&corners)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn triangulate_four_points_gives_two_triangles() {
+        let points = [
+            AxialPos { q: 0, r: 0 },
+            AxialPos { q: 3, r: 0 },
+            AxialPos { q: 0, r: 3 },
+            AxialPos { q: 3, r: 3 },
+        ];
+        let triangles = triangulate(&points, &GRID_SIZE, HexCoordSystem::Row);
+        assert_eq!(triangles.len(), 2);
+        for triangle in triangles.iter() {
+            let corners = triangle.map(|i| {
+                points[i].center_in_world_given_coord_system(&GRID_SIZE, HexCoordSystem::Row)
+            });
+            assert!(polygon_area(&corners) > 0.0);
+        }
+    }
+
+    #[test]
+    fn triangulate_covers_convex_hull() {
+        let points = [
+            AxialPos { q: -4, r: 7 },
+            AxialPos { q: -1, r: -7 },
+            AxialPos { q: 1, r: -2 },
+            AxialPos { q: 2, r: -1 },
+            AxialPos { q: 6, r: 6 },
+        ];
+        let triangles = triangulate(&points, &GRID_SIZE, HexCoordSystem::Row);
+        let hull = convex_hull(&points, &GRID_SIZE, HexCoordSystem::Row);
+        assert_eq!(triangles.len(), 2 * points.len() - hull.len() - 2);
+        let area = triangulated_area(&points, &triangles);
+        assert!((area - polygon_area(&hull)).abs() < 1e-2 * area);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..200 {
+            let points: Vec<AxialPos> = (0..rng.gen_range(3..12))
+                .map(|_| AxialPos {
+                    q: rng.gen_range(0..16),
+                    r: rng.gen_range(0..16),
+                })
+                .collect();
+            let triangles = triangulate(&points, &GRID_SIZE, HexCoordSystem::Row);
+            let hull_area =
+                polygon_area(&convex_hull(&points, &GRID_SIZE, HexCoordSystem::Row)).max(0.0);
+            let area = triangulated_area(&points, &triangles);
+            for triangle in triangles.iter() {
+                let [a, b, c] = triangle.map(|i| {
+                    points[i].center_in_world_given_coord_system(&GRID_SIZE, HexCoordSystem::Row)
+                });
+                let center = circumcenter(a, b, c);
+                let radius = center.distance(a);
+                for point in points.iter() {
+                    let p =
+                        point.center_in_world_given_coord_system(&GRID_SIZE, HexCoordSystem::Row);
+                    assert!(center.distance(p) > radius - 1e-2, "{:?}", points);
+                }
+            }
+            assert!(
+                (area - hull_area).abs() <= 1e-3 * hull_area.max(1.0),
+                "{:?}: {} != {}",
+                points,
+                area,
+                hull_area
+            );
+        }
+    }
+
+    #[test]
+    fn bounding_hex_encloses_every_tile() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..100 {
+            let tiles: Vec<AxialPos> = (0..rng.gen_range(1..12))
+                .map(|_| AxialPos {
+                    q: rng.gen_range(-10..10),
+                    r: rng.gen_range(-10..10),
+                })
+                .collect();
+            let (center, radius) = bounding_hex(&tiles).unwrap();
+            for tile in tiles.iter() {
+                assert!(tile.distance_from(&center) as u32 <= radius);
+            }
+        }
+        assert_eq!(bounding_hex(&[]), None);
+    }
+
+    #[test]
+    fn stamp_near_the_edge_of_the_map() {
+        let map_size = TilemapSize { x: 4, y: 4 };
+        let pattern = [AxialPos { q: 0, r: 0 }, AxialPos { q: 1, r: 0 }];
+        let at = AxialPos { q: 3, r: 1 };
+
+        let clipped = stamp(&pattern, at, HexCoordSystem::Row, &map_size, false);
+        assert_eq!(clipped, vec![TilePos { x: 3, y: 1 }]);
+        assert!(stamp(&pattern, at, HexCoordSystem::Row, &map_size, true).is_empty());
+
+        let inside = AxialPos { q: 2, r: 1 };
+        let expected = vec![TilePos { x: 2, y: 1 }, TilePos { x: 3, y: 1 }];
+        assert_eq!(
+            stamp(&pattern, inside, HexCoordSystem::Row, &map_size, true),
+            expected
+        );
+    }
+
+    #[test]
+    fn weighted_centroid_with_equal_weights_is_the_plain_centroid() {
+        let tiles = [
+            AxialPos { q: 0, r: 0 },
+            AxialPos { q: 3, r: -1 },
+            AxialPos { q: -2, r: 4 },
+            AxialPos { q: 1, r: 1 },
+        ];
+        let weighted: Vec<(AxialPos, f32)> = tiles.iter().map(|tile| (*tile, 2.5)).collect();
+        let centroid = weighted_centroid(&weighted).unwrap();
+        assert!((centroid.q - 0.5).abs() < 1e-6);
+        assert!((centroid.r - 1.0).abs() < 1e-6);
+
+        let zero: Vec<(AxialPos, f32)> = tiles.iter().map(|tile| (*tile, 0.0)).collect();
+        assert!(weighted_centroid(&zero).is_none());
+    }
+
+    #[test]
+    fn weighted_centroid_moves_toward_the_heavier_tile() {
+        let (light, heavy) = (AxialPos { q: 0, r: 0 }, AxialPos { q: 4, r: -2 });
+        let centroid = weighted_centroid(&[(light, 1.0), (heavy, 3.0)]).unwrap();
+        assert!((centroid.q - 3.0).abs() < 1e-6);
+        assert!((centroid.r + 1.5).abs() < 1e-6);
+        assert!(
+            centroid.distance_from(&FractionalAxialPos::from(heavy))
+                < centroid.distance_from(&FractionalAxialPos::from(light))
+        );
+    }
+
+    #[test]
+    fn adjacent_pairs_of_a_two_by_two_block() {
+        let tiles: HashSet<AxialPos> = [(0, 0), (1, 0), (0, 1), (1, 1)]
+            .into_iter()
+            .map(|(q, r)| AxialPos { q, r })
+            .collect();
+        let pairs = adjacent_pairs(&tiles);
+        assert_eq!(pairs.len(), 5);
+
+        let unordered: HashSet<(AxialPos, AxialPos)> = pairs
+            .iter()
+            .map(|&(a, b)| if a < b { (a, b) } else { (b, a) })
+            .collect();
+        assert_eq!(unordered.len(), 5);
+        for (a, b) in pairs {
+            assert_eq!(a.distance_from(&b), 1);
+        }
+    }
+
+    #[test]
+    fn convex_hull_of_a_triangle() {
+        let corners = [
+            AxialPos { q: 0, r: 0 },
+            AxialPos { q: 4, r: 0 },
+            AxialPos { q: 0, r: 4 },
+        ];
+        let mut tiles = corners.to_vec();
+        tiles.extend([
+            AxialPos { q: 1, r: 1 },
+            AxialPos { q: 2, r: 0 },
+            AxialPos { q: 2, r: 2 },
+            AxialPos { q: 4, r: 0 },
+        ]);
+
+        let hull = convex_hull(&tiles, &GRID_SIZE, HexCoordSystem::Row);
+        let expected: Vec<Vec2> = corners
+            .iter()
+            .map(|corner| corner.center_in_world_row(&GRID_SIZE))
+            .collect();
+        assert_eq!(hull, expected);
+        assert!(polygon_area(&hull) > 0.0);
+    }
+
+    const MAP_SIZE: TilemapSize = TilemapSize { x: 8, y: 8 };
+
+    #[test]
+    fn voronoi_labels_tiles_by_nearest_seed() {
+        let seeds = [(AxialPos { q: 1, r: 3 }, 7), (AxialPos { q: 5, r: 3 }, 2)];
+        let labels = voronoi(&seeds, HexCoordSystem::Row, &MAP_SIZE);
+        assert_eq!(labels.len(), MAP_SIZE.count());
+        assert_eq!(labels[&TilePos { x: 1, y: 3 }], 7);
+        assert_eq!(labels[&TilePos { x: 5, y: 3 }], 2);
+        // The midpoint is equally far from both seeds, so the lowest label wins.
+        assert_eq!(labels[&TilePos { x: 3, y: 3 }], 2);
+
+        for (tile_pos, label) in labels.iter() {
+            let axial_pos = AxialPos::from(tile_pos);
+            let nearest = seeds
+                .iter()
+                .min_by_key(|(seed, label)| (seed.distance_from(&axial_pos), *label))
+                .unwrap();
+            assert_eq!(*label, nearest.1);
+        }
+    }
+
+    #[test]
+    fn voronoi_bounded_regions_are_split_by_a_wall() {
+        // The left seed is nearer to the tiles just right of the wall, but cannot reach them.
+        let seeds = [(AxialPos { q: 3, r: 3 }, 1), (AxialPos { q: 7, r: 3 }, 2)];
+        let passable = |tile_pos: TilePos| tile_pos.x != 4;
+        let labels = voronoi_bounded(&seeds, passable, HexCoordSystem::Row, &MAP_SIZE);
+
+        for y in 0..MAP_SIZE.y {
+            for x in 0..MAP_SIZE.x {
+                let expected = match x {
+                    0..=3 => Some(1),
+                    4 => None,
+                    _ => Some(2),
+                };
+                assert_eq!(labels.get(&TilePos { x, y }).copied(), expected);
+            }
+        }
+    }
+
+    fn tile_set(coords: &[(i32, i32)]) -> HashSet<AxialPos> {
+        coords.iter().map(|&(q, r)| AxialPos { q, r }).collect()
+    }
+
+    #[test]
+    fn is_connected_for_connected_and_split_sets() {
+        assert!(is_connected(&tile_set(&[(0, 0), (1, 0), (1, 1), (0, 2)])));
+        assert!(!is_connected(&tile_set(&[(0, 0), (1, 0), (3, 0)])));
+        assert!(is_connected(&HashSet::default()));
+    }
+
+    #[test]
+    fn connected_components_of_two_groups() {
+        let left = tile_set(&[(0, 0), (1, 0), (0, 1)]);
+        let right = tile_set(&[(4, 0), (4, 1)]);
+        let tiles: HashSet<AxialPos> = left.union(&right).copied().collect();
+
+        let mut components = connected_components(&tiles);
+        components.sort_by_key(|component| component.len());
+        assert_eq!(components, vec![right, left]);
+    }
+
+    #[test]
+    fn region_diameter_of_a_line() {
+        for n in 1..8 {
+            let line: HashSet<AxialPos> = (0..n).map(|q| AxialPos { q, r: 0 }).collect();
+            assert_eq!(region_diameter(&line), n as u32 - 1);
+        }
+        assert_eq!(region_diameter(&HashSet::default()), 0);
+    }
+
+    #[test]
+    fn region_center_of_symmetric_regions() {
+        let center = AxialPos { q: 3, r: -2 };
+        let disc: HashSet<AxialPos> = center.range(2).into_iter().collect();
+        assert_eq!(region_center(&disc), Some(center));
+
+        let line: HashSet<AxialPos> = (-3..=3).map(|r| AxialPos { q: 1, r }).collect();
+        assert_eq!(region_center(&line), Some(AxialPos { q: 1, r: 0 }));
+        assert_eq!(region_center(&HashSet::default()), None);
+    }
+
+    #[test]
+    fn border_between_gives_the_frontier_pairs() {
+        let region_a = tile_set(&[(0, 0), (0, 1), (-1, 1), (0, 2)]);
+        let region_b = tile_set(&[(1, 0), (2, 0), (1, 2)]);
+        let mut border = border_between(&region_a, &region_b);
+        border.sort();
+
+        let pair = |a: (i32, i32), b: (i32, i32)| {
+            (AxialPos { q: a.0, r: a.1 }, AxialPos { q: b.0, r: b.1 })
+        };
+        assert_eq!(
+            border,
+            vec![
+                pair((0, 0), (1, 0)),
+                pair((0, 1), (1, 0)),
+                pair((0, 2), (1, 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn enclosed_area_of_a_ring() {
+        let center = AxialPos { q: 3, r: 3 };
+        let ring: HashSet<AxialPos> = center.ring(1).into_iter().collect();
+        let inside = enclosed_area(&ring, HexCoordSystem::Row, &MAP_SIZE).unwrap();
+        assert_eq!(inside, tile_set(&[(3, 3)]));
+
+        let wide_ring: HashSet<AxialPos> = center.ring(2).into_iter().collect();
+        let inside = enclosed_area(&wide_ring, HexCoordSystem::Row, &MAP_SIZE).unwrap();
+        assert_eq!(inside, center.range(1).into_iter().collect());
+
+        let mut broken = ring;
+        broken.remove(&(center + AxialPos { q: 1, r: 0 }));
+        assert_eq!(enclosed_area(&broken, HexCoordSystem::Row, &MAP_SIZE), None);
+    }
+
+    #[test]
+    fn poisson_disk_keeps_the_minimum_distance() {
+        let min_distance = 3;
+        let mut rng = StdRng::seed_from_u64(5);
+        let samples = poisson_disk(min_distance, HexCoordSystem::Row, &MAP_SIZE, &mut rng);
+        assert!(!samples.is_empty());
+
+        let axial: Vec<AxialPos> = samples
+            .iter()
+            .map(|tile_pos| {
+                AxialPos::from_tile_pos_given_coord_system(tile_pos, HexCoordSystem::Row)
+            })
+            .collect();
+        for (i, a) in axial.iter().enumerate() {
+            for b in axial[i + 1..].iter() {
+                assert!(a.distance_from(b) >= min_distance as i32);
+            }
+        }
+
+        // No further tile could be added.
+        for y in 0..MAP_SIZE.y {
+            for x in 0..MAP_SIZE.x {
+                let tile = AxialPos::from_tile_pos_given_coord_system(
+                    &TilePos { x, y },
+                    HexCoordSystem::Row,
+                );
+                assert!(axial
+                    .iter()
+                    .any(|sample| sample.distance_from(&tile) < min_distance as i32));
+            }
+        }
+    }
+
+    fn dumbbell() -> HashSet<AxialPos> {
+        let mut tiles: HashSet<AxialPos> = AxialPos { q: 0, r: 0 }.range(1).into_iter().collect();
+        tiles.extend(AxialPos { q: 6, r: 0 }.range(1));
+        tiles.extend(tile_set(&[(2, 0), (3, 0), (4, 0)]));
+        tiles
+    }
+
+    #[test]
+    fn articulation_points_of_a_dumbbell_are_its_neck() {
+        let expected = tile_set(&[(1, 0), (2, 0), (3, 0), (4, 0), (5, 0)]);
+        assert_eq!(articulation_points(&dumbbell()), expected);
+
+        // Every tile of a ring can be removed without splitting it.
+        let ring: HashSet<AxialPos> = AxialPos { q: 0, r: 0 }.ring(2).into_iter().collect();
+        assert!(articulation_points(&ring).is_empty());
+    }
+
+    fn unordered(pairs: Vec<(AxialPos, AxialPos)>) -> HashSet<(AxialPos, AxialPos)> {
+        pairs
+            .into_iter()
+            .map(|(a, b)| (a.min(b), a.max(b)))
+            .collect()
+    }
+
+    #[test]
+    fn bridges_of_a_single_edge_and_a_dumbbell() {
+        let pair = tile_set(&[(0, 0), (1, 0)]);
+        assert_eq!(
+            unordered(bridges(&pair)),
+            unordered(vec![(AxialPos { q: 0, r: 0 }, AxialPos { q: 1, r: 0 })])
+        );
+
+        let neck: Vec<(AxialPos, AxialPos)> = (1..5)
+            .map(|q| (AxialPos { q, r: 0 }, AxialPos { q: q + 1, r: 0 }))
+            .collect();
+        assert_eq!(unordered(bridges(&dumbbell())), unordered(neck));
+
+        let ring: HashSet<AxialPos> = AxialPos { q: 0, r: 0 }.ring(2).into_iter().collect();
+        assert!(bridges(&ring).is_empty());
+    }
+
+    #[test]
+    fn path_betweenness_is_highest_in_a_corridor() {
+        let left = AxialPos { q: 0, r: 0 }.ring(1);
+        let right = AxialPos { q: 6, r: 0 }.ring(1);
+        let pairs: Vec<(AxialPos, AxialPos)> =
+            left.iter().copied().zip(right.iter().copied()).collect();
+
+        let counts = path_betweenness(&dumbbell(), &pairs);
+        let corridor = AxialPos { q: 3, r: 0 };
+        assert_eq!(counts[&corridor], pairs.len() as u32);
+        assert!(counts.values().all(|count| *count <= counts[&corridor]));
+        // Unreachable pairs are skipped.
+        assert!(path_betweenness(&dumbbell(), &[(corridor, AxialPos { q: 20, r: 0 })]).is_empty());
+    }
+
+    #[test]
+    fn loop_winding_of_opposite_loops_is_opposite() {
+        for hex_coord_sys in [HexCoordSystem::Row, HexCoordSystem::Column] {
+            let ring = AxialPos { q: 0, r: 0 }.ring(1);
+            let forward = loop_winding(&ring, &GRID_SIZE, hex_coord_sys);
+            let reversed: Vec<AxialPos> = ring.iter().rev().copied().collect();
+            let backward = loop_winding(&reversed, &GRID_SIZE, hex_coord_sys);
+            assert_ne!(forward, Ordering::Equal);
+            assert_eq!(backward, forward.reverse());
+        }
+
+        let line = [
+            AxialPos { q: 0, r: 0 },
+            AxialPos { q: 1, r: 0 },
+            AxialPos { q: 2, r: 0 },
+        ];
+        assert_eq!(
+            loop_winding(&line, &GRID_SIZE, HexCoordSystem::Row),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn voronoi_borders_follow_the_dividing_line() {
+        let mut labels: HashMap<TilePos, u32> = HashMap::default();
+        for y in 0..MAP_SIZE.y {
+            for x in 0..MAP_SIZE.x {
+                labels.insert(TilePos { x, y }, (x >= 4) as u32);
+            }
+        }
+
+        let mut expected = Vec::new();
+        for y in 0..MAP_SIZE.y {
+            expected.push((TilePos { x: 3, y }, TilePos { x: 4, y }));
+            if y + 1 < MAP_SIZE.y {
+                expected.push((TilePos { x: 4, y }, TilePos { x: 3, y: y + 1 }));
+            }
+        }
+        assert_eq!(
+            voronoi_borders(&labels, HexCoordSystem::Row, &MAP_SIZE),
+            expected
+        );
+
+        for label in labels.values_mut() {
+            *label = 0;
+        }
+        assert!(voronoi_borders(&labels, HexCoordSystem::Row, &MAP_SIZE).is_empty());
+    }
+}
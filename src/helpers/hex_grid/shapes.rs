@@ -0,0 +1,336 @@
+use crate::helpers::hex_grid::axial::AxialPos;
+use crate::helpers::hex_grid::neighbors::{HexDirection, HEX_OFFSETS};
+
+/// The direction in which the first tile of a ring lies, relative to the ring's center.
+const RING_START_DIRECTION: HexDirection = HexDirection::Four;
+
+/// Returns the spiral index of the first tile of the ring with the given `radius`.
+fn ring_start_index(radius: u32) -> u32 {
+    if radius == 0 {
+        0
+    } else {
+        1 + 3 * radius * (radius - 1)
+    }
+}
+
+impl AxialPos {
+    /// Returns all tiles lying at most `radius` away from `self`, including `self`.
+    ///
+    /// See the Red Blob Games article on [range](https://www.redblobgames.com/grids/hexagons/#range)
+    /// for more information.
+    pub fn range(&self, radius: u32) -> Vec<AxialPos> {
+        let n = radius as i32;
+        let mut tiles = Vec::with_capacity(ring_start_index(radius + 1) as usize);
+        for q in -n..=n {
+            for r in (-n).max(-q - n)..=n.min(-q + n) {
+                tiles.push(*self + AxialPos { q, r });
+            }
+        }
+        tiles
+    }
+
+    /// Returns the tiles whose cube coordinates differ from those of `self` by at most `q_rad`,
+    /// `r_rad`, and `s_rad` respectively.
+    ///
+    /// This is the intersection of three slabs, one along each axis, which produces hexagonal or
+    /// trapezoidal shapes depending on the radii. When all three radii are equal, this is the same
+    /// as [`range`](AxialPos::range).
+    pub fn cube_box(&self, q_rad: u32, r_rad: u32, s_rad: u32) -> Vec<AxialPos> {
+        let (q_rad, r_rad, s_rad) = (q_rad as i32, r_rad as i32, s_rad as i32);
+        let mut tiles = Vec::new();
+        for q in -q_rad..=q_rad {
+            for r in -r_rad..=r_rad {
+                if (q + r).abs() <= s_rad {
+                    tiles.push(*self + AxialPos { q, r });
+                }
+            }
+        }
+        tiles
+    }
+
+    /// Returns the tiles lying exactly `radius` away from `self`.
+    ///
+    /// Tiles are returned in counter-clockwise order, starting with the tile lying
+    /// [`HexDirection::Four`] of `self`. If `radius` is `0`, then only `self` is returned.
+    pub fn ring(&self, radius: u32) -> Vec<AxialPos> {
+        if radius == 0 {
+            return vec![*self];
+        }
+
+        let mut tiles = Vec::with_capacity(6 * radius as usize);
+        let mut current = *self + (radius as i32) * HEX_OFFSETS[RING_START_DIRECTION as usize];
+        for offset in HEX_OFFSETS.iter() {
+            for _ in 0..radius {
+                tiles.push(current);
+                current = current + *offset;
+            }
+        }
+        tiles
+    }
+
+    /// Returns the tiles lying at least `inner` and at most `outer` away from `self`.
+    ///
+    /// This is the set difference of [`range(outer)`](AxialPos::range) and
+    /// `range(inner - 1)`. Tiles are returned ring by ring, from the inside out.
+    ///
+    /// Panics if `inner > outer`.
+    pub fn annulus(&self, inner: u32, outer: u32) -> Vec<AxialPos> {
+        assert!(
+            inner <= outer,
+            "inner radius ({}) must not exceed outer radius ({})",
+            inner,
+            outer
+        );
+        (inner..=outer)
+            .flat_map(|radius| self.ring(radius))
+            .collect()
+    }
+
+    /// Returns the tiles lying at least `radius` and less than `radius + thickness` away from
+    /// `self`, ring by ring from the inside out.
+    ///
+    /// This is the same as [`annulus(radius, radius + thickness - 1)`](AxialPos::annulus). A
+    /// `thickness` of `1` gives [`ring(radius)`](AxialPos::ring), while a `thickness` of `0` gives
+    /// no tiles.
+    pub fn thick_ring(&self, radius: u32, thickness: u32) -> Vec<AxialPos> {
+        if thickness == 0 {
+            return Vec::new();
+        }
+        self.annulus(radius, radius + thickness - 1)
+    }
+
+    /// Returns `self` followed by three arms of `arm_length` tiles each, extending from `self` in
+    /// directions [`HexDirection::Zero`], [`HexDirection::Two`], and [`HexDirection::Four`].
+    ///
+    /// This is the hex analog of a plus shape. Each arm is listed from `self` outwards. See
+    /// [`x_shape`](AxialPos::x_shape) for the arms extending in the other three directions.
+    pub fn plus_shape(&self, arm_length: u32) -> Vec<AxialPos> {
+        self.arms(
+            [HexDirection::Zero, HexDirection::Two, HexDirection::Four],
+            arm_length,
+        )
+    }
+
+    /// Returns `self` followed by three arms of `arm_length` tiles each, extending from `self` in
+    /// directions [`HexDirection::One`], [`HexDirection::Three`], and [`HexDirection::Five`].
+    ///
+    /// This is [`plus_shape`](AxialPos::plus_shape) rotated by `pi/3`.
+    pub fn x_shape(&self, arm_length: u32) -> Vec<AxialPos> {
+        self.arms(
+            [HexDirection::One, HexDirection::Three, HexDirection::Five],
+            arm_length,
+        )
+    }
+
+    /// Returns `self` followed by the arms of `arm_length` tiles extending from `self` in each of
+    /// the given `directions`.
+    fn arms(&self, directions: [HexDirection; 3], arm_length: u32) -> Vec<AxialPos> {
+        let mut tiles = Vec::with_capacity(1 + 3 * arm_length as usize);
+        tiles.push(*self);
+        for direction in directions {
+            let offset = HEX_OFFSETS[direction as usize];
+            tiles.extend((1..=arm_length as i32).map(|k| *self + k * offset));
+        }
+        tiles
+    }
+
+    /// Returns the tiles of the rings around `self` (see [`ring`](AxialPos::ring)) at radii
+    /// `step`, `2 * step`, and so on, up to `max_radius`, ring by ring from the inside out.
+    ///
+    /// A `step` of `1` gives every tile within `max_radius` of `self`, except `self`. A `step` of
+    /// `0` gives no tiles.
+    pub fn stepped_rings(&self, max_radius: u32, step: u32) -> Vec<AxialPos> {
+        if step == 0 {
+            return Vec::new();
+        }
+        (step..=max_radius)
+            .step_by(step as usize)
+            .flat_map(|radius| self.ring(radius))
+            .collect()
+    }
+
+    /// Returns the index of `self` in the outward spiral ordering of hexes around `(0, 0)`.
+    ///
+    /// The origin has index `0`, the first ring occupies indices `1..=6`, the second ring occupies
+    /// indices `7..=18`, and so on. Within each ring, tiles are ordered as in
+    /// [`ring`](AxialPos::ring).
+    ///
+    /// This is the inverse of [`from_spiral_index`](AxialPos::from_spiral_index).
+    pub fn spiral_index(&self) -> u32 {
+        let radius = self.magnitude();
+        if radius == 0 {
+            return 0;
+        }
+
+        let mut corner = radius * HEX_OFFSETS[RING_START_DIRECTION as usize];
+        for (side, offset) in HEX_OFFSETS.iter().enumerate() {
+            let delta = *self - corner;
+            let step = delta.magnitude();
+            if step < radius && step * *offset == delta {
+                return ring_start_index(radius as u32) + (side as i32 * radius + step) as u32;
+            }
+            corner = corner + radius * *offset;
+        }
+
+        unreachable!("a hex with non-zero magnitude always lies on one of the sides of its ring")
+    }
+
+    /// Returns the hex with the given index in the outward spiral ordering of hexes around
+    /// `(0, 0)`.
+    ///
+    /// This is the inverse of [`spiral_index`](AxialPos::spiral_index).
+    pub fn from_spiral_index(index: u32) -> AxialPos {
+        if index == 0 {
+            return AxialPos { q: 0, r: 0 };
+        }
+
+        let mut radius = 1;
+        while ring_start_index(radius + 1) <= index {
+            radius += 1;
+        }
+
+        let index_in_ring = index - ring_start_index(radius);
+        let side = (index_in_ring / radius) as usize;
+        let step = (index_in_ring % radius) as i32;
+        let radius = radius as i32;
+
+        let corner = HEX_OFFSETS[..side].iter().fold(
+            radius * HEX_OFFSETS[RING_START_DIRECTION as usize],
+            |corner, offset| corner + radius * *offset,
+        );
+        corner + step * HEX_OFFSETS[side]
+    }
+}
+
+/// Returns the hexes of a hexagonal map of radius `outer_radius` around `(0, 0)` with a hexagonal
+/// hole of radius `inner_radius` in its center, ring by ring from the inside out.
+///
+/// These are the hexes lying more than `inner_radius` and at most `outer_radius` away from the
+/// origin, so an `inner_radius` of `0` removes only the origin itself. Returns an empty vector if
+/// `inner_radius >= outer_radius`.
+pub fn donut_map(outer_radius: u32, inner_radius: u32) -> Vec<AxialPos> {
+    if inner_radius >= outer_radius {
+        return Vec::new();
+    }
+    AxialPos { q: 0, r: 0 }.annulus(inner_radius + 1, outer_radius)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::utils::HashSet;
+
+    #[test]
+    fn spiral_index_round_trips_over_the_first_rings() {
+        let origin = AxialPos { q: 0, r: 0 };
+        let mut index = 0;
+        for radius in 0..=5 {
+            for tile in origin.ring(radius) {
+                assert_eq!(tile.spiral_index(), index);
+                assert_eq!(AxialPos::from_spiral_index(index), tile);
+                index += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn annulus_count_and_no_inner_tiles() {
+        let center = AxialPos { q: 2, r: -1 };
+        for inner in 0..4 {
+            for outer in inner..6 {
+                let tiles = center.annulus(inner, outer);
+                let expected = 3 * outer * (outer + 1) + 1 - ring_start_index(inner);
+                assert_eq!(tiles.len() as u32, expected);
+                for tile in tiles.iter() {
+                    let distance = tile.distance_from(&center) as u32;
+                    assert!(inner <= distance && distance <= outer);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cube_box_with_equal_radii_is_a_range() {
+        let center = AxialPos { q: -1, r: 3 };
+        for radius in 0..5 {
+            let mut cube_box = center.cube_box(radius, radius, radius);
+            let mut range = center.range(radius);
+            cube_box.sort();
+            range.sort();
+            assert_eq!(cube_box, range);
+        }
+        // A zero `s` radius leaves a single diagonal line.
+        assert_eq!(center.cube_box(2, 2, 0).len(), 5);
+    }
+
+    #[test]
+    fn thick_ring_count() {
+        let center = AxialPos { q: 1, r: -2 };
+        let tiles = center.thick_ring(2, 3);
+        assert_eq!(tiles.len(), 6 * (2 + 3 + 4));
+        assert!(tiles
+            .iter()
+            .all(|tile| (2..=4).contains(&tile.distance_from(&center))));
+        let unique: HashSet<AxialPos> = tiles.iter().copied().collect();
+        assert_eq!(unique.len(), tiles.len());
+
+        assert_eq!(center.thick_ring(0, 2).len(), 1 + 6);
+        assert_eq!(center.thick_ring(3, 1), center.ring(3));
+        assert!(center.thick_ring(3, 0).is_empty());
+
+        let thin = center.thick_ring(20, 1).len() as f32;
+        let thick = center.thick_ring(20, 2).len() as f32;
+        assert!((thick / thin - 2.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn plus_shape_with_arms_of_one_tile() {
+        let center = AxialPos { q: 2, r: 1 };
+        let plus = center.plus_shape(1);
+        assert_eq!(
+            plus,
+            vec![
+                center,
+                center + HEX_OFFSETS[HexDirection::Zero as usize],
+                center + HEX_OFFSETS[HexDirection::Two as usize],
+                center + HEX_OFFSETS[HexDirection::Four as usize],
+            ]
+        );
+        assert_eq!(center.plus_shape(3).len(), 10);
+        assert_eq!(center.plus_shape(0), vec![center]);
+    }
+
+    #[test]
+    fn donut_map_count() {
+        let origin = AxialPos { q: 0, r: 0 };
+        for (outer, inner) in [(3, 1), (4, 0), (5, 3)] {
+            let donut = donut_map(outer, inner);
+            assert_eq!(
+                donut.len(),
+                origin.range(outer).len() - origin.range(inner).len()
+            );
+            assert!(donut.iter().all(|tile| {
+                let distance = tile.distance_from(&origin) as u32;
+                inner < distance && distance <= outer
+            }));
+        }
+        assert!(donut_map(2, 2).is_empty());
+    }
+
+    #[test]
+    fn stepped_rings_with_a_step_of_two_are_even() {
+        let center = AxialPos { q: -1, r: 2 };
+        let rings = center.stepped_rings(5, 2);
+        let expected: Vec<AxialPos> = [2, 4]
+            .into_iter()
+            .flat_map(|radius| center.ring(radius))
+            .collect();
+        assert_eq!(rings, expected);
+        assert!(rings
+            .iter()
+            .all(|tile| tile.distance_from(&center) % 2 == 0));
+
+        assert_eq!(center.stepped_rings(3, 1).len(), center.range(3).len() - 1);
+        assert!(center.stepped_rings(3, 0).is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+use crate::helpers::hex_grid::axial::AxialPos;
+use crate::helpers::hex_grid::cube::CubePos;
+
+/// The three axes of a hex grid, named after their corresponding [`CubePos`] coordinate.
+///
+/// Reflecting across an axis keeps the corresponding cube coordinate fixed, and swaps the other
+/// two.
+#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum HexAxis {
+    Q,
+    R,
+    S,
+}
+
+impl AxialPos {
+    /// Reflects `self` across the `q` axis through the origin: `q` is kept, while `r` and `s` are
+    /// swapped.
+    pub fn reflect_q(&self) -> AxialPos {
+        let CubePos { q, s, .. } = CubePos::from(*self);
+        AxialPos { q, r: s }
+    }
+
+    /// Reflects `self` across the `r` axis through the origin: `r` is kept, while `q` and `s` are
+    /// swapped.
+    pub fn reflect_r(&self) -> AxialPos {
+        let CubePos { r, s, .. } = CubePos::from(*self);
+        AxialPos { q: s, r }
+    }
+
+    /// Reflects `self` across the `s` axis through the origin: `s` is kept, while `q` and `r` are
+    /// swapped.
+    pub fn reflect_s(&self) -> AxialPos {
+        AxialPos {
+            q: self.r,
+            r: self.q,
+        }
+    }
+
+    /// Rotates `self` around the origin by `steps` multiples of `pi/3`. Positive `steps` rotate
+    /// counter-clockwise (in the same sense as increasing
+    /// [`HexDirection`](crate::helpers::hex_grid::neighbors::HexDirection)s), while negative `steps`
+    /// rotate clockwise.
+    pub fn rotate(&self, steps: i32) -> AxialPos {
+        let mut cube_pos = CubePos::from(*self);
+        for _ in 0..steps.rem_euclid(6) {
+            let CubePos { q, r, s } = cube_pos;
+            cube_pos = CubePos {
+                q: -r,
+                r: -s,
+                s: -q,
+            };
+        }
+        cube_pos.into()
+    }
+
+    /// Returns the positions obtained by rotating `self` around the origin by every multiple of
+    /// `2 * pi / fold`, starting with `self` itself.
+    ///
+    /// Only folds which divide `6` (`1`, `2`, `3`, and `6`) are supported; `None` is returned for
+    /// any other fold.
+    pub fn symmetry_orbit(&self, fold: u32) -> Option<Vec<AxialPos>> {
+        if fold == 0 || 6 % fold != 0 {
+            return None;
+        }
+        let step = (6 / fold) as i32;
+        Some((0..fold as i32).map(|i| self.rotate(i * step)).collect())
+    }
+
+    /// Rotates `self` by `pi` about `center`, i.e. reflects it through the point `center`.
+    ///
+    /// This is [`rotate(3)`](AxialPos::rotate) about `center` rather than the origin, so distances
+    /// and adjacency are preserved. Reflecting twice gives back `self`.
+    pub fn point_reflect(&self, center: &AxialPos) -> AxialPos {
+        2 * *center - *self
+    }
+
+    /// Reflects `self` across the given `axis` through the origin.
+    pub fn reflect(&self, axis: HexAxis) -> AxialPos {
+        match axis {
+            HexAxis::Q => self.reflect_q(),
+            HexAxis::R => self.reflect_r(),
+            HexAxis::S => self.reflect_s(),
+        }
+    }
+}
+
+/// Reflects every tile of `path` across the given `axis` through the origin.
+///
+/// Reflection preserves distances, so adjacent tiles remain adjacent and the path keeps its
+/// length. Reflecting twice across the same axis returns the original path.
+pub fn reflect_path(path: &[AxialPos], axis: HexAxis) -> Vec<AxialPos> {
+    path.iter().map(|tile| tile.reflect(axis)).collect()
+}
+
+/// Returns how far `values` is from having `fold`-fold rotational symmetry around the origin, as
+/// the mean squared difference between the value of each tile of `sample` and the values of the
+/// other tiles of its [`symmetry_orbit`](AxialPos::symmetry_orbit).
+///
+/// A score of `0.0` means that `values` is perfectly symmetric over `sample`. An empty `sample`,
+/// or a `fold` of `1`, also scores `0.0`.
+///
+/// Panics if `fold` does not divide `6`.
+pub fn symmetry_score(values: impl Fn(AxialPos) -> f32, fold: u32, sample: &[AxialPos]) -> f32 {
+    assert!(
+        fold != 0 && 6 % fold == 0,
+        "{} is not a supported fold",
+        fold
+    );
+
+    let mut total = 0.0;
+    let mut count = 0;
+    for tile in sample.iter() {
+        let orbit = tile.symmetry_orbit(fold).unwrap();
+        let value = values(*tile);
+        for rotated in orbit.iter().skip(1) {
+            let difference = value - values(*rotated);
+            total += difference * difference;
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        0.0
+    } else {
+        total / count as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_reflect_preserves_adjacency() {
+        let center = AxialPos { q: 2, r: -1 };
+        for tile in center.range(3) {
+            let reflected = tile.point_reflect(&center);
+            assert_eq!(reflected.point_reflect(&center), tile);
+            assert_eq!(
+                reflected.distance_from(&center),
+                tile.distance_from(&center)
+            );
+            for neighbor in tile.neighbors() {
+                assert_eq!(neighbor.point_reflect(&center).distance_from(&reflected), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn reflect_path_twice_is_the_identity() {
+        let path = AxialPos { q: -2, r: 1 }.line_to(&AxialPos { q: 3, r: -3 });
+        for axis in [HexAxis::Q, HexAxis::R, HexAxis::S] {
+            let reflected = reflect_path(&path, axis);
+            assert_eq!(reflect_path(&reflected, axis), path);
+            for step in reflected.windows(2) {
+                assert_eq!(step[0].distance_from(&step[1]), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn symmetry_orbit_of_fold_six_is_equidistant() {
+        let origin = AxialPos { q: 0, r: 0 };
+        let tile = AxialPos { q: 3, r: -1 };
+        let orbit = tile.symmetry_orbit(6).unwrap();
+        assert_eq!(orbit.len(), 6);
+        assert_eq!(orbit[0], tile);
+
+        let distinct: std::collections::HashSet<AxialPos> = orbit.iter().copied().collect();
+        assert_eq!(distinct.len(), 6);
+        for rotated in orbit.iter() {
+            assert_eq!(rotated.distance_from(&origin), tile.distance_from(&origin));
+        }
+        for (i, rotated) in orbit.iter().enumerate() {
+            let next = orbit[(i + 1) % 6];
+            assert_eq!(
+                rotated.distance_from(&next),
+                orbit[0].distance_from(&orbit[1])
+            );
+        }
+        assert!(tile.symmetry_orbit(4).is_none());
+    }
+
+    #[test]
+    fn symmetry_score_of_symmetric_and_asymmetric_values() {
+        let origin = AxialPos { q: 0, r: 0 };
+        let sample = origin.range(3);
+        let radial = |tile: AxialPos| tile.distance_from(&origin) as f32;
+        let slope = |tile: AxialPos| tile.q as f32;
+
+        for fold in [1, 2, 3, 6] {
+            assert_eq!(symmetry_score(radial, fold, &sample), 0.0);
+        }
+        assert!(symmetry_score(slope, 6, &sample) > 0.0);
+        assert!(symmetry_score(slope, 2, &sample) > 0.0);
+        assert_eq!(symmetry_score(slope, 1, &sample), 0.0);
+        assert_eq!(symmetry_score(slope, 6, &[]), 0.0);
+    }
+}
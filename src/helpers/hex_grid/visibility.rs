@@ -0,0 +1,376 @@
+use crate::helpers::hex_grid::axial::AxialPos;
+use crate::helpers::hex_grid::neighbors::{HexDirection, HEX_OFFSETS};
+use crate::map::HexCoordSystem;
+use crate::tiles::TilePos;
+use crate::{TilemapGridSize, TilemapSize};
+use bevy::utils::HashSet;
+use fixedbitset::FixedBitSet;
+use std::f32::consts::TAU;
+
+/// Returns `true` if no tile strictly between `origin` and `target` on the hex line joining them
+/// `blocks` sight. Intermediate tiles lying outside of `map_size` do not block sight.
+fn has_line_of_sight(
+    origin: &AxialPos,
+    target: &AxialPos,
+    blocks: &impl Fn(TilePos) -> bool,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> bool {
+    let line = origin.line_to(target);
+    line.iter()
+        .skip(1)
+        .take(line.len().saturating_sub(2))
+        .filter_map(|axial_pos| axial_pos.as_tile_pos_given_coord_system(map_size, hex_coord_sys))
+        .all(|tile_pos| !blocks(tile_pos))
+}
+
+/// Returns the tiles within `radius` of `origin` which are visible from `origin`.
+///
+/// A tile is visible if no tile strictly between it and `origin`, on the hex line joining them,
+/// `blocks` sight. Blocking tiles can themselves be visible. `origin` is always visible.
+pub fn compute_fov(
+    origin: TilePos,
+    radius: u32,
+    blocks: impl Fn(TilePos) -> bool,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashSet<TilePos> {
+    let origin_axial = AxialPos::from_tile_pos_given_coord_system(&origin, hex_coord_sys);
+    origin_axial
+        .range(radius)
+        .into_iter()
+        .filter(|target| has_line_of_sight(&origin_axial, target, &blocks, hex_coord_sys, map_size))
+        .filter_map(|target| target.as_tile_pos_given_coord_system(map_size, hex_coord_sys))
+        .collect()
+}
+
+/// Returns the tiles within `radius` of `origin` which are visible from `origin` (see
+/// [`compute_fov`]), and which lie in the cone of sectors centered on `facing`.
+///
+/// A tile lies in the cone if its sector (see [`AxialPos::sector_from`]) is at most
+/// `half_angle_sectors` sectors away from `facing`. A `half_angle_sectors` of `0` restricts
+/// visibility to the single `pi/3` sector centered on `facing`, while `3` or more is equivalent to
+/// the full field of view. `origin` is always visible.
+pub fn cone_fov(
+    origin: TilePos,
+    facing: HexDirection,
+    half_angle_sectors: u32,
+    radius: u32,
+    blocks: impl Fn(TilePos) -> bool,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> HashSet<TilePos> {
+    let origin_axial = AxialPos::from_tile_pos_given_coord_system(&origin, hex_coord_sys);
+    compute_fov(origin, radius, blocks, hex_coord_sys, map_size)
+        .into_iter()
+        .filter(|tile_pos| {
+            let axial_pos = AxialPos::from_tile_pos_given_coord_system(tile_pos, hex_coord_sys);
+            if axial_pos == origin_axial {
+                return true;
+            }
+            let sector = axial_pos.sector_from(&origin_axial) as u32;
+            let delta = (sector + 6 - facing as u32) % 6;
+            delta.min(6 - delta) <= half_angle_sectors
+        })
+        .collect()
+}
+
+/// Returns the tiles hit by a straight beam `width` tiles wide, fired from `origin` in the given
+/// `direction`.
+///
+/// The beam's center steps away from `origin` (which is not included) one tile at a time, and at
+/// each step, the tiles lying across the beam are added. The beam stops at the first center tile
+/// which `blocks` it (that tile is included), or at the edge of the map.
+///
+/// A `width` of `1` only includes the center tiles, like a raycast. Wider beams extend
+/// `width / 2` tiles sideways towards `direction + 1`, and `(width - 1) / 2` tiles towards the
+/// opposite side. A `width` of `0` hits nothing.
+pub fn beam(
+    origin: TilePos,
+    direction: HexDirection,
+    width: u32,
+    blocks: impl Fn(TilePos) -> bool,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> Vec<TilePos> {
+    let mut tiles = Vec::new();
+    if width == 0 {
+        return tiles;
+    }
+
+    let forward = HEX_OFFSETS[direction as usize];
+    let side = HEX_OFFSETS[(direction + 1) as usize];
+    let (left, right) = ((width / 2) as i32, ((width - 1) / 2) as i32);
+
+    let mut center = AxialPos::from_tile_pos_given_coord_system(&origin, hex_coord_sys);
+    loop {
+        center = center + forward;
+        let center_tile_pos = match center.as_tile_pos_given_coord_system(map_size, hex_coord_sys) {
+            Some(center_tile_pos) => center_tile_pos,
+            None => break,
+        };
+
+        for k in -right..=left {
+            if let Some(tile_pos) =
+                (center + k * side).as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+            {
+                tiles.push(tile_pos);
+            }
+        }
+
+        if blocks(center_tile_pos) {
+            break;
+        }
+    }
+    tiles
+}
+
+/// Returns the tiles within `radius` of `origin` which are visible from `origin` (see
+/// [`compute_fov`]), as a bitset indexed by [`TilePos::to_index`].
+///
+/// The bitset has one bit per tile of the map, which makes it much cheaper to store and combine
+/// than a `HashSet`.
+pub fn fov_bitset(
+    origin: TilePos,
+    radius: u32,
+    blocks: impl Fn(TilePos) -> bool,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> FixedBitSet {
+    let mut visible = FixedBitSet::with_capacity(map_size.count());
+    let origin_axial = AxialPos::from_tile_pos_given_coord_system(&origin, hex_coord_sys);
+    for target in origin_axial.range(radius) {
+        if let Some(tile_pos) = target.as_tile_pos_given_coord_system(map_size, hex_coord_sys) {
+            if has_line_of_sight(&origin_axial, &target, &blocks, hex_coord_sys, map_size) {
+                visible.insert(tile_pos.to_index(map_size));
+            }
+        }
+    }
+    visible
+}
+
+/// Returns `true` if `target` can be seen from an eye placed `observer_height` above the terrain at
+/// `observer`, given the terrain `height` of each tile.
+///
+/// The sight line runs from the eye to the terrain at `target`. Sight is blocked if any tile
+/// strictly between `observer` and `target`, on the hex line joining them, rises above the sight
+/// line where it passes over that tile. Intermediate tiles lying outside of `map_size` do not block
+/// sight.
+pub fn los_heightfield(
+    observer: TilePos,
+    observer_height: f32,
+    target: TilePos,
+    height: impl Fn(TilePos) -> f32,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> bool {
+    let line = AxialPos::from_tile_pos_given_coord_system(&observer, hex_coord_sys).line_to(
+        &AxialPos::from_tile_pos_given_coord_system(&target, hex_coord_sys),
+    );
+    let steps = (line.len() - 1) as f32;
+    let eye = height(observer) + observer_height;
+    let rise = height(target) - eye;
+
+    line.iter()
+        .enumerate()
+        .skip(1)
+        .take(line.len().saturating_sub(2))
+        .filter_map(|(i, axial_pos)| {
+            axial_pos
+                .as_tile_pos_given_coord_system(map_size, hex_coord_sys)
+                .map(|tile_pos| (i, tile_pos))
+        })
+        .all(|(i, tile_pos)| height(tile_pos) <= eye + rise * i as f32 / steps)
+}
+
+/// Returns the tiles within `radius` of `origin` whose bearing from `origin` (see
+/// [`AxialPos::bearing_to`]) lies in the wedge from `start_angle` to `start_angle + sweep_angle`,
+/// sweeping counter-clockwise.
+///
+/// Angles are in radians. A `sweep_angle` of `2 * pi` or more covers the full disc of radius
+/// `radius`. `origin` is always included, and tiles lying outside of `map_size` are omitted.
+pub fn radar_sweep(
+    origin: TilePos,
+    start_angle: f32,
+    sweep_angle: f32,
+    radius: u32,
+    grid_size: &TilemapGridSize,
+    hex_coord_sys: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> Vec<TilePos> {
+    let origin_axial = AxialPos::from_tile_pos_given_coord_system(&origin, hex_coord_sys);
+    origin_axial
+        .range(radius)
+        .into_iter()
+        .filter(|axial_pos| {
+            *axial_pos == origin_axial
+                || sweep_angle >= TAU
+                || (origin_axial.bearing_to(axial_pos, grid_size, hex_coord_sys) - start_angle)
+                    .rem_euclid(TAU)
+                    <= sweep_angle
+        })
+        .filter_map(|axial_pos| axial_pos.as_tile_pos_given_coord_system(map_size, hex_coord_sys))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP_SIZE: TilemapSize = TilemapSize { x: 9, y: 9 };
+    const ORIGIN: TilePos = TilePos { x: 4, y: 4 };
+
+    #[test]
+    fn narrow_cone_fov_excludes_the_sides() {
+        let cone = cone_fov(
+            ORIGIN,
+            HexDirection::Zero,
+            0,
+            3,
+            |_| false,
+            HexCoordSystem::Row,
+            &MAP_SIZE,
+        );
+        assert!(cone.contains(&ORIGIN));
+        assert!(cone.contains(&TilePos { x: 7, y: 4 }));
+        assert!(!cone.contains(&TilePos { x: 3, y: 4 }));
+        assert!(!cone.contains(&TilePos { x: 4, y: 5 }));
+        assert!(!cone.contains(&TilePos { x: 5, y: 3 }));
+
+        let origin_axial = AxialPos::from(&ORIGIN);
+        for tile_pos in cone.iter().filter(|tile_pos| **tile_pos != ORIGIN) {
+            let sector = AxialPos::from(tile_pos).sector_from(&origin_axial);
+            assert_eq!(sector, HexDirection::Zero);
+        }
+
+        let full = cone_fov(
+            ORIGIN,
+            HexDirection::Zero,
+            3,
+            3,
+            |_| false,
+            HexCoordSystem::Row,
+            &MAP_SIZE,
+        );
+        assert_eq!(
+            full,
+            compute_fov(ORIGIN, 3, |_| false, HexCoordSystem::Row, &MAP_SIZE)
+        );
+    }
+
+    #[test]
+    fn beam_of_width_one_is_a_raycast() {
+        let wall = TilePos { x: 7, y: 4 };
+        let blocks = |tile_pos: TilePos| tile_pos == wall;
+        let ray = beam(
+            ORIGIN,
+            HexDirection::Zero,
+            1,
+            blocks,
+            HexCoordSystem::Row,
+            &MAP_SIZE,
+        );
+        assert_eq!(
+            ray,
+            vec![TilePos { x: 5, y: 4 }, TilePos { x: 6, y: 4 }, wall]
+        );
+
+        let wide = beam(
+            ORIGIN,
+            HexDirection::Zero,
+            3,
+            blocks,
+            HexCoordSystem::Row,
+            &MAP_SIZE,
+        );
+        assert_eq!(wide.len(), 9);
+        assert!(ray.iter().all(|tile_pos| wide.contains(tile_pos)));
+        assert!(wide.contains(&TilePos { x: 5, y: 5 }));
+        assert!(wide.contains(&TilePos { x: 5, y: 3 }));
+
+        let nothing = beam(
+            ORIGIN,
+            HexDirection::Zero,
+            0,
+            blocks,
+            HexCoordSystem::Row,
+            &MAP_SIZE,
+        );
+        assert!(nothing.is_empty());
+    }
+
+    #[test]
+    fn fov_bitset_matches_compute_fov() {
+        let wall = |tile_pos: TilePos| tile_pos.x == 5 && tile_pos.y != 4;
+        let expected = compute_fov(ORIGIN, 4, wall, HexCoordSystem::Row, &MAP_SIZE);
+        let bitset = fov_bitset(ORIGIN, 4, wall, HexCoordSystem::Row, &MAP_SIZE);
+
+        assert_eq!(bitset.len(), MAP_SIZE.count());
+        let visible: HashSet<TilePos> = bitset
+            .ones()
+            .filter_map(|index| TilePos::from_index(index, &MAP_SIZE))
+            .collect();
+        assert_eq!(visible, expected);
+    }
+
+    #[test]
+    fn los_heightfield_blocked_by_a_hill_but_not_a_valley() {
+        let (observer, target) = (TilePos { x: 0, y: 4 }, TilePos { x: 8, y: 4 });
+        let terrain = |middle_height: f32| {
+            move |tile_pos: TilePos| {
+                if tile_pos == ORIGIN {
+                    middle_height
+                } else {
+                    0.0
+                }
+            }
+        };
+        let visible = |observer_height: f32, middle_height: f32| {
+            los_heightfield(
+                observer,
+                observer_height,
+                target,
+                terrain(middle_height),
+                HexCoordSystem::Row,
+                &MAP_SIZE,
+            )
+        };
+
+        assert!(visible(1.0, 0.0));
+        assert!(!visible(1.0, 3.0));
+        assert!(visible(1.0, -3.0));
+        // A tall enough observer sees over the hill.
+        assert!(visible(10.0, 3.0));
+    }
+
+    #[test]
+    fn full_radar_sweep_is_the_full_disc() {
+        let grid_size = TilemapGridSize { x: 16.0, y: 16.0 };
+        let sweep = |start_angle: f32, sweep_angle: f32| -> HashSet<TilePos> {
+            radar_sweep(
+                ORIGIN,
+                start_angle,
+                sweep_angle,
+                3,
+                &grid_size,
+                HexCoordSystem::Row,
+                &MAP_SIZE,
+            )
+            .into_iter()
+            .collect()
+        };
+
+        let disc: HashSet<TilePos> = AxialPos::from(&ORIGIN)
+            .range(3)
+            .into_iter()
+            .filter_map(|axial_pos| axial_pos.as_tile_pos(&MAP_SIZE))
+            .collect();
+        assert_eq!(sweep(0.0, TAU), disc);
+        assert_eq!(sweep(1.0, TAU), disc);
+
+        let half = sweep(0.0, TAU / 2.0);
+        assert!(half.len() < disc.len());
+        assert!(half.is_subset(&disc));
+        assert_eq!(sweep(0.5, 0.0).len(), 1);
+    }
+}
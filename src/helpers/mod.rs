@@ -1,3 +1,6 @@
+pub mod biome;
+pub mod bitset;
+pub mod field;
 pub mod filling;
 pub mod geometry;
 pub mod hex_grid;
@@ -5,7 +5,9 @@ use crate::helpers::iso_grid::staggered::StaggeredPos;
 use crate::map::{HexCoordSystem, IsoCoordSystem};
 use crate::tiles::TilePos;
 use crate::{TilemapGridSize, TilemapSize, TilemapType};
-use bevy::math::Vec2;
+use bevy::math::{Vec2, Vec3};
+use bevy::prelude::GlobalTransform;
+use bevy::sprite::Rect;
 
 impl TilePos {
     /// Get the center of this tile in world space.
@@ -58,41 +60,463 @@ impl TilePos {
         grid_size: &TilemapGridSize,
         map_type: &TilemapType,
     ) -> Option<TilePos> {
-        match map_type {
-            TilemapType::Square { .. } => {
-                let x = ((world_pos.x / grid_size.x) + 0.5).floor() as i32;
-                let y = ((world_pos.y / grid_size.y) + 0.5).floor() as i32;
+        let (x, y) = world_pos_to_i32_pair(world_pos, grid_size, map_type);
+        TilePos::from_i32_pair(x, y, map_size)
+    }
+}
+
+/// Converts a tile position from one hexagonal coordinate system into another.
+///
+/// `pos` is interpreted according to `from`, converted into an [`AxialPos`], and then expressed
+/// according to `to`. This is useful when migrating tile data between maps which use different
+/// [`HexCoordSystem`]s.
+///
+/// Returns `None` if the converted position lies out of the bounds of `map_size`.
+pub fn convert_coord_system(
+    pos: TilePos,
+    from: HexCoordSystem,
+    to: HexCoordSystem,
+    map_size: &TilemapSize,
+) -> Option<TilePos> {
+    AxialPos::from_tile_pos_given_coord_system(&pos, from)
+        .as_tile_pos_given_coord_system(map_size, to)
+}
 
-                TilePos::from_i32_pair(x, y, map_size)
+/// Returns the unbounded tile coordinates of the tile containing `world_pos`.
+fn world_pos_to_i32_pair(
+    world_pos: &Vec2,
+    grid_size: &TilemapGridSize,
+    map_type: &TilemapType,
+) -> (i32, i32) {
+    match map_type {
+        TilemapType::Square { .. } => (
+            ((world_pos.x / grid_size.x) + 0.5).floor() as i32,
+            ((world_pos.y / grid_size.y) + 0.5).floor() as i32,
+        ),
+        TilemapType::Hexagon(hex_coord_sys) => match hex_coord_sys {
+            HexCoordSystem::RowEven => {
+                let RowEvenPos { q, r } = RowEvenPos::from_world_pos(world_pos, grid_size);
+                (q, r)
             }
-            TilemapType::Hexagon(hex_coord_sys) => match hex_coord_sys {
-                HexCoordSystem::RowEven => {
-                    RowEvenPos::from_world_pos(world_pos, grid_size).as_tile_pos(map_size)
-                }
-                HexCoordSystem::RowOdd => {
-                    RowOddPos::from_world_pos(world_pos, grid_size).as_tile_pos(map_size)
-                }
-                HexCoordSystem::ColumnEven => {
-                    ColEvenPos::from_world_pos(world_pos, grid_size).as_tile_pos(map_size)
-                }
-                HexCoordSystem::ColumnOdd => {
-                    ColOddPos::from_world_pos(world_pos, grid_size).as_tile_pos(map_size)
-                }
-                HexCoordSystem::Row => {
-                    AxialPos::from_world_pos_row(world_pos, grid_size).as_tile_pos(map_size)
+            HexCoordSystem::RowOdd => {
+                let RowOddPos { q, r } = RowOddPos::from_world_pos(world_pos, grid_size);
+                (q, r)
+            }
+            HexCoordSystem::ColumnEven => {
+                let ColEvenPos { q, r } = ColEvenPos::from_world_pos(world_pos, grid_size);
+                (q, r)
+            }
+            HexCoordSystem::ColumnOdd => {
+                let ColOddPos { q, r } = ColOddPos::from_world_pos(world_pos, grid_size);
+                (q, r)
+            }
+            HexCoordSystem::Row => {
+                let AxialPos { q, r } = AxialPos::from_world_pos_row(world_pos, grid_size);
+                (q, r)
+            }
+            HexCoordSystem::Column => {
+                let AxialPos { q, r } = AxialPos::from_world_pos_col(world_pos, grid_size);
+                (q, r)
+            }
+        },
+        TilemapType::Isometric { coord_system, .. } => match coord_system {
+            IsoCoordSystem::Diamond => {
+                let DiamondPos { x, y } = DiamondPos::from_world_pos(world_pos, grid_size);
+                (x, y)
+            }
+            IsoCoordSystem::Staggered => {
+                let StaggeredPos { x, y } = StaggeredPos::from_world_pos(world_pos, grid_size);
+                (x, y)
+            }
+        },
+    }
+}
+
+/// Returns the inclusive range of tile positions, as a `(min_tile, max_tile)` pair, covering the
+/// given world space `aabb`. The range is clamped to the bounds of `map_size`.
+///
+/// For hexagonal and isometric maps, tile rows and columns are skewed with respect to the world
+/// axes, so the range is widened by a one-tile margin in every direction.
+///
+/// Returns `None` if `aabb` lies entirely outside of the map.
+pub fn world_aabb_to_tile_range(
+    aabb: Rect,
+    grid_size: &TilemapGridSize,
+    map_type: &TilemapType,
+    map_size: &TilemapSize,
+) -> Option<(TilePos, TilePos)> {
+    let corners = [
+        aabb.min,
+        Vec2::new(aabb.max.x, aabb.min.y),
+        Vec2::new(aabb.min.x, aabb.max.y),
+        aabb.max,
+    ];
+
+    let (mut min_x, mut min_y) = (i32::MAX, i32::MAX);
+    let (mut max_x, mut max_y) = (i32::MIN, i32::MIN);
+    for corner in corners.iter() {
+        let (x, y) = world_pos_to_i32_pair(corner, grid_size, map_type);
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+
+    if !matches!(map_type, TilemapType::Square { .. }) {
+        min_x -= 1;
+        min_y -= 1;
+        max_x += 1;
+        max_y += 1;
+    }
+
+    if max_x < 0 || max_y < 0 || min_x >= map_size.x as i32 || min_y >= map_size.y as i32 {
+        return None;
+    }
+
+    let min_tile = TilePos {
+        x: min_x.max(0) as u32,
+        y: min_y.max(0) as u32,
+    };
+    let max_tile = TilePos {
+        x: (max_x as u32).min(map_size.x - 1),
+        y: (max_y as u32).min(map_size.y - 1),
+    };
+    Some((min_tile, max_tile))
+}
+
+/// Returns the tile hit by the ray starting at `ray_origin` and travelling along `ray_dir`, both
+/// given in world space.
+///
+/// The ray is transformed into the local space of the map using `map_transform`, and intersected
+/// with the map's `z = 0` plane. The hit position is then converted into a tile position.
+///
+/// Returns `None` if the ray is parallel to the map plane, if the map plane lies behind the ray's
+/// origin, or if the hit position lies outside of the map.
+pub fn ray_to_tile(
+    ray_origin: Vec3,
+    ray_dir: Vec3,
+    map_transform: &GlobalTransform,
+    grid_size: &TilemapGridSize,
+    map_type: &TilemapType,
+    map_size: &TilemapSize,
+) -> Option<TilePos> {
+    let world_to_map = map_transform.affine().inverse();
+    let local_origin = world_to_map.transform_point3(ray_origin);
+    let local_dir = world_to_map.transform_vector3(ray_dir);
+
+    if local_dir.z.abs() <= f32::EPSILON {
+        return None;
+    }
+
+    let t = -local_origin.z / local_dir.z;
+    if t < 0.0 {
+        return None;
+    }
+
+    let hit = local_origin + t * local_dir;
+    TilePos::from_world_pos(&hit.truncate(), map_size, grid_size, map_type)
+}
+
+/// Returns the tiles stepping from `from` to `to`, excluding `from` and including `to`.
+///
+/// For hexagonal maps, this follows [`AxialPos::line_to`], so consecutive tiles are neighbors.
+/// For other maps, `x` and `y` are interpolated together, so consecutive tiles are neighbors
+/// including diagonals. Tiles lying outside of `map_size` are skipped.
+fn tile_steps(
+    from: TilePos,
+    to: TilePos,
+    map_type: &TilemapType,
+    map_size: &TilemapSize,
+) -> Vec<TilePos> {
+    match map_type {
+        TilemapType::Hexagon(hex_coord_sys) => {
+            let from = AxialPos::from_tile_pos_given_coord_system(&from, *hex_coord_sys);
+            let to = AxialPos::from_tile_pos_given_coord_system(&to, *hex_coord_sys);
+            from.line_to(&to)
+                .into_iter()
+                .skip(1)
+                .filter_map(|axial_pos| {
+                    axial_pos.as_tile_pos_given_coord_system(map_size, *hex_coord_sys)
+                })
+                .collect()
+        }
+        _ => {
+            let (dx, dy) = (to.x as i32 - from.x as i32, to.y as i32 - from.y as i32);
+            let n = dx.abs().max(dy.abs());
+            (1..=n)
+                .filter_map(|i| {
+                    let t = i as f32 / n as f32;
+                    let x = from.x as i32 + (t * dx as f32).round() as i32;
+                    let y = from.y as i32 + (t * dy as f32).round() as i32;
+                    TilePos::from_i32_pair(x, y, map_size)
+                })
+                .collect()
+        }
+    }
+}
+
+/// Returns the tiles along the cubic Bezier curve with control points `p0`, `p1`, `p2` and `p3`,
+/// given in world space, in order from `p0` to `p3`.
+///
+/// The curve is sampled at `samples + 1` evenly spaced parameters, and each sample is snapped to
+/// the tile containing it. Gaps between the tiles of consecutive samples are filled in, using
+/// [`AxialPos::line_to`] for hexagonal maps, so that the returned tiles form a connected path.
+/// Samples lying outside of `map_size` are skipped.
+#[allow(clippy::too_many_arguments)]
+pub fn bezier_tiles(
+    p0: Vec2,
+    p1: Vec2,
+    p2: Vec2,
+    p3: Vec2,
+    grid_size: &TilemapGridSize,
+    map_type: &TilemapType,
+    map_size: &TilemapSize,
+    samples: u32,
+) -> Vec<TilePos> {
+    let samples = samples.max(1);
+    let mut tiles: Vec<TilePos> = Vec::new();
+    for i in 0..=samples {
+        let t = i as f32 / samples as f32;
+        let u = 1.0 - t;
+        let point = u * u * u * p0 + 3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t * p3;
+        let tile_pos = match TilePos::from_world_pos(&point, map_size, grid_size, map_type) {
+            Some(tile_pos) => tile_pos,
+            None => continue,
+        };
+        match tiles.last() {
+            Some(last) if *last == tile_pos => {}
+            Some(last) => {
+                let steps = tile_steps(*last, tile_pos, map_type, map_size);
+                tiles.extend(steps);
+            }
+            None => tiles.push(tile_pos),
+        }
+    }
+    tiles
+}
+
+/// Returns the tiles whose world space center lies inside the polygon with the given `vertices`,
+/// in row-major order.
+///
+/// The polygon is closed automatically, and may be concave or self-intersecting, in which case
+/// the even-odd rule decides which points lie inside. Only tiles within the range given by
+/// [`world_aabb_to_tile_range`] for the polygon's bounding box are tested. Returns no tiles if
+/// there are fewer than three vertices.
+///
+/// Tile centers lying exactly on the boundary are classified by a half-open rule, rather than
+/// being strictly inside: a center on a left or bottom edge is inside, while a center on a right
+/// or top edge is not. Polygons which share an edge therefore never both contain a tile. Polygons
+/// whose edges run through tile centers may contain more tiles than a strict test would suggest:
+/// for example, a diamond traced through the centers of an isometric map picks up the centers on
+/// two of its sides.
+pub fn tiles_in_polygon(
+    vertices: &[Vec2],
+    grid_size: &TilemapGridSize,
+    map_type: &TilemapType,
+    map_size: &TilemapSize,
+) -> Vec<TilePos> {
+    if vertices.len() < 3 {
+        return Vec::new();
+    }
+
+    let (min, max) = vertices.iter().fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(min, max), vertex| (min.min(*vertex), max.max(*vertex)),
+    );
+    let (min_tile, max_tile) =
+        match world_aabb_to_tile_range(Rect { min, max }, grid_size, map_type, map_size) {
+            Some(range) => range,
+            None => return Vec::new(),
+        };
+
+    // Casts a ray from `point` towards positive `x`, and counts the edges it crosses.
+    let contains = |point: Vec2| {
+        let mut inside = false;
+        let mut previous = vertices[vertices.len() - 1];
+        for current in vertices.iter() {
+            if (current.y > point.y) != (previous.y > point.y) {
+                let crossing_x = current.x
+                    + (point.y - current.y) * (previous.x - current.x) / (previous.y - current.y);
+                if point.x < crossing_x {
+                    inside = !inside;
                 }
-                HexCoordSystem::Column => {
-                    AxialPos::from_world_pos_col(world_pos, grid_size).as_tile_pos(map_size)
+            }
+            previous = *current;
+        }
+        inside
+    };
+
+    let mut tiles = Vec::new();
+    for y in min_tile.y..=max_tile.y {
+        for x in min_tile.x..=max_tile.x {
+            let tile_pos = TilePos { x, y };
+            if contains(tile_pos.center_in_world(grid_size, map_type)) {
+                tiles.push(tile_pos);
+            }
+        }
+    }
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRID_SIZE: TilemapGridSize = TilemapGridSize { x: 16.0, y: 16.0 };
+    const MAP_SIZE: TilemapSize = TilemapSize { x: 8, y: 8 };
+    const MAP_TYPE: TilemapType = TilemapType::Square {
+        diagonal_neighbors: false,
+    };
+
+    fn square(min: f32, max: f32) -> [Vec2; 4] {
+        [
+            Vec2::new(min, min),
+            Vec2::new(max, min),
+            Vec2::new(max, max),
+            Vec2::new(min, max),
+        ]
+    }
+
+    #[test]
+    fn tiles_in_polygon_fills_an_exact_square() {
+        let tiles = tiles_in_polygon(&square(8.0, 56.0), &GRID_SIZE, &MAP_TYPE, &MAP_SIZE);
+        let expected: Vec<TilePos> = (1..=3)
+            .flat_map(|y| (1..=3).map(move |x| TilePos { x, y }))
+            .collect();
+        assert_eq!(tiles, expected);
+    }
+
+    #[test]
+    fn tiles_in_polygon_includes_only_the_left_and_bottom_boundary() {
+        let tiles = tiles_in_polygon(&square(16.0, 48.0), &GRID_SIZE, &MAP_TYPE, &MAP_SIZE);
+        let expected: Vec<TilePos> = (1..=2)
+            .flat_map(|y| (1..=2).map(move |x| TilePos { x, y }))
+            .collect();
+        assert_eq!(tiles, expected);
+    }
+
+    #[test]
+    fn convert_coord_system_round_trips() {
+        let systems = [
+            HexCoordSystem::Row,
+            HexCoordSystem::RowEven,
+            HexCoordSystem::RowOdd,
+            HexCoordSystem::Column,
+            HexCoordSystem::ColumnEven,
+            HexCoordSystem::ColumnOdd,
+        ];
+        for from in systems {
+            for to in systems {
+                let mut converted = 0;
+                for y in 0..MAP_SIZE.y {
+                    for x in 0..MAP_SIZE.x {
+                        let pos = TilePos { x, y };
+                        if let Some(other) = convert_coord_system(pos, from, to, &MAP_SIZE) {
+                            converted += 1;
+                            assert_eq!(convert_coord_system(other, to, from, &MAP_SIZE), Some(pos));
+                        }
+                    }
                 }
-            },
-            TilemapType::Isometric { coord_system, .. } => match coord_system {
-                IsoCoordSystem::Diamond => {
-                    DiamondPos::from_world_pos(world_pos, grid_size).as_tile_pos(map_size)
+                if from == to {
+                    assert_eq!(converted, MAP_SIZE.count());
                 }
-                IsoCoordSystem::Staggered => {
-                    StaggeredPos::from_world_pos(world_pos, grid_size).as_tile_pos(map_size)
+            }
+        }
+    }
+
+    #[test]
+    fn world_aabb_to_tile_range_clamps_a_partial_view() {
+        let view = Rect {
+            min: Vec2::new(-40.0, -40.0),
+            max: Vec2::new(40.0, 24.0),
+        };
+        assert_eq!(
+            world_aabb_to_tile_range(view, &GRID_SIZE, &MAP_TYPE, &MAP_SIZE),
+            Some((TilePos { x: 0, y: 0 }, TilePos { x: 3, y: 2 }))
+        );
+
+        let outside = Rect {
+            min: Vec2::new(-80.0, -80.0),
+            max: Vec2::new(-40.0, -40.0),
+        };
+        assert_eq!(
+            world_aabb_to_tile_range(outside, &GRID_SIZE, &MAP_TYPE, &MAP_SIZE),
+            None
+        );
+
+        let hex_map_type = TilemapType::Hexagon(HexCoordSystem::RowOdd);
+        let (min_tile, max_tile) =
+            world_aabb_to_tile_range(view, &GRID_SIZE, &hex_map_type, &MAP_SIZE).unwrap();
+        for y in 0..MAP_SIZE.y {
+            for x in 0..MAP_SIZE.x {
+                let tile_pos = TilePos { x, y };
+                let center = tile_pos.center_in_world(&GRID_SIZE, &hex_map_type);
+                if view.min.cmple(center).all() && center.cmple(view.max).all() {
+                    assert!(min_tile.x <= x && x <= max_tile.x);
+                    assert!(min_tile.y <= y && y <= max_tile.y);
                 }
-            },
+            }
         }
     }
+
+    #[test]
+    fn ray_to_tile_for_a_downward_ray() {
+        let down = Vec3::new(0.0, 0.0, -1.0);
+        let identity = GlobalTransform::identity();
+        assert_eq!(
+            ray_to_tile(
+                Vec3::new(48.0, 32.0, 100.0),
+                down,
+                &identity,
+                &GRID_SIZE,
+                &MAP_TYPE,
+                &MAP_SIZE
+            ),
+            Some(TilePos { x: 3, y: 2 })
+        );
+
+        let shifted = GlobalTransform::from(bevy::prelude::Transform::from_xyz(16.0, 0.0, -10.0));
+        assert_eq!(
+            ray_to_tile(
+                Vec3::new(48.0, 32.0, 100.0),
+                down,
+                &shifted,
+                &GRID_SIZE,
+                &MAP_TYPE,
+                &MAP_SIZE
+            ),
+            Some(TilePos { x: 2, y: 2 })
+        );
+
+        assert_eq!(
+            ray_to_tile(
+                Vec3::new(48.0, 32.0, 100.0),
+                -down,
+                &identity,
+                &GRID_SIZE,
+                &MAP_TYPE,
+                &MAP_SIZE
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn straight_bezier_tiles_are_a_straight_line() {
+        // Control points evenly spaced along a row of tile centers.
+        let points = [0.0, 40.0, 72.0, 112.0].map(|x| Vec2::new(x, 32.0));
+        let tiles = bezier_tiles(
+            points[0], points[1], points[2], points[3], &GRID_SIZE, &MAP_TYPE, &MAP_SIZE, 16,
+        );
+        let expected: Vec<TilePos> = (0..8).map(|x| TilePos { x, y: 2 }).collect();
+        assert_eq!(tiles, expected);
+
+        let hex = TilemapType::Hexagon(HexCoordSystem::Row);
+        let points = [0.0, 40.0, 72.0, 112.0].map(|x| Vec2::new(x, 0.0));
+        let tiles = bezier_tiles(
+            points[0], points[1], points[2], points[3], &GRID_SIZE, &hex, &MAP_SIZE, 16,
+        );
+        let expected: Vec<TilePos> = (0..8).map(|x| TilePos { x, y: 0 }).collect();
+        assert_eq!(tiles, expected);
+    }
 }
@@ -1 +1,184 @@
+use crate::tiles::TilePos;
+use crate::TilemapSize;
+use bevy::math::{UVec2, Vec2};
 
+/// Returns the tiles selected by placing the boolean `mask` at `anchor`, in the order of `mask`.
+///
+/// `anchor` is the top-left tile of the mask: `mask[row][column]` corresponds with the tile at
+/// `(anchor.x + column, anchor.y - row)`, since `y` increases upwards. Rows may have different
+/// lengths. Only tiles whose entry is `true`, and which lie within `map_size`, are returned.
+pub fn stamp_mask(mask: &[Vec<bool>], anchor: TilePos, map_size: &TilemapSize) -> Vec<TilePos> {
+    let mut tiles = Vec::new();
+    for (row, entries) in mask.iter().enumerate() {
+        for (column, selected) in entries.iter().enumerate() {
+            if !selected {
+                continue;
+            }
+            let (x, y) = match (
+                anchor.x.checked_add(column as u32),
+                anchor.y.checked_sub(row as u32),
+            ) {
+                (Some(x), Some(y)) => (x, y),
+                _ => continue,
+            };
+            let tile_pos = TilePos { x, y };
+            if tile_pos.within_map_bounds(map_size) {
+                tiles.push(tile_pos);
+            }
+        }
+    }
+    tiles
+}
+
+/// Returns the position of the corner with the smallest `x` and `y` of the best free placement of
+/// a rectangle of `footprint` tiles: one in which no tile of the rectangle is `occupied`, and the
+/// whole rectangle lies within `map_size`.
+///
+/// Among the free placements, the one whose center lies nearest to the center of the map is
+/// chosen, with ties broken in favor of the first in row-major order. Occupancy is counted with a
+/// summed-area table, so each tile of the map is only queried once.
+///
+/// Returns `None` if no placement is free, or if either component of `footprint` is `0`.
+pub fn best_fit_rect(
+    footprint: UVec2,
+    occupied: impl Fn(TilePos) -> bool,
+    map_size: &TilemapSize,
+) -> Option<TilePos> {
+    if footprint.x == 0 || footprint.y == 0 || footprint.x > map_size.x || footprint.y > map_size.y
+    {
+        return None;
+    }
+
+    // `sums[y][x]` holds the number of occupied tiles with coordinates below `(x, y)`.
+    let (width, height) = (map_size.x as usize, map_size.y as usize);
+    let mut sums = vec![vec![0u32; width + 1]; height + 1];
+    for y in 0..height {
+        for x in 0..width {
+            let tile_occupied = occupied(TilePos {
+                x: x as u32,
+                y: y as u32,
+            }) as u32;
+            sums[y + 1][x + 1] = tile_occupied + sums[y][x + 1] + sums[y + 1][x] - sums[y][x];
+        }
+    }
+
+    let (footprint_x, footprint_y) = (footprint.x as usize, footprint.y as usize);
+    let map_center = Vec2::new(map_size.x as f32, map_size.y as f32) / 2.0;
+    let mut best = None;
+    let mut best_distance = f32::INFINITY;
+    for y in 0..=(height - footprint_y) {
+        for x in 0..=(width - footprint_x) {
+            let (x1, y1) = (x + footprint_x, y + footprint_y);
+            let count = sums[y1][x1] + sums[y][x] - sums[y][x1] - sums[y1][x];
+            if count > 0 {
+                continue;
+            }
+            let center = Vec2::new(x as f32, y as f32) + footprint.as_vec2() / 2.0;
+            let distance = center.distance_squared(map_center);
+            if distance < best_distance {
+                best_distance = distance;
+                best = Some(TilePos {
+                    x: x as u32,
+                    y: y as u32,
+                });
+            }
+        }
+    }
+    best
+}
+
+/// Returns every tile of the map exactly once, in a pseudo-random order which depends only on
+/// `seed`. This is useful for effects such as revealing the map tile by tile.
+///
+/// Tiles are sorted by a hash of their index and `seed`. Since the hash is a bijection of the
+/// index, no two tiles share a hash, so the order is fully determined.
+pub fn shuffled_tiles(map_size: &TilemapSize, seed: u64) -> Vec<TilePos> {
+    // SplitMix64 finalizer, which is a bijection on `u64`.
+    let hash = |index: u64| {
+        let mut h = (seed ^ index).wrapping_add(0x9E37_79B9_7F4A_7C15);
+        h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        h ^ (h >> 31)
+    };
+
+    let mut tiles: Vec<TilePos> = Vec::with_capacity(map_size.count());
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            tiles.push(TilePos { x, y });
+        }
+    }
+    tiles.sort_by_key(|tile_pos| hash(tile_pos.to_index(map_size) as u64));
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP_SIZE: TilemapSize = TilemapSize { x: 6, y: 5 };
+
+    #[test]
+    fn stamp_mask_of_a_plus() {
+        let plus = vec![
+            vec![false, true, false],
+            vec![true, true, true],
+            vec![false, true],
+        ];
+        assert_eq!(
+            stamp_mask(&plus, TilePos { x: 1, y: 3 }, &MAP_SIZE),
+            vec![
+                TilePos { x: 2, y: 3 },
+                TilePos { x: 1, y: 2 },
+                TilePos { x: 2, y: 2 },
+                TilePos { x: 3, y: 2 },
+                TilePos { x: 2, y: 1 },
+            ]
+        );
+
+        // Near the corner of the map, only the tiles within it are kept.
+        assert_eq!(
+            stamp_mask(&plus, TilePos { x: 4, y: 1 }, &MAP_SIZE),
+            vec![
+                TilePos { x: 5, y: 1 },
+                TilePos { x: 4, y: 0 },
+                TilePos { x: 5, y: 0 },
+            ]
+        );
+        assert!(stamp_mask(&plus, TilePos { x: u32::MAX, y: 1 }, &MAP_SIZE).is_empty());
+    }
+
+    #[test]
+    fn best_fit_rect_finds_the_gap() {
+        let footprint = UVec2::new(2, 2);
+        let in_gap = |tile_pos: TilePos| tile_pos.x < 2 && tile_pos.y >= 3;
+        assert_eq!(
+            best_fit_rect(footprint, |tile_pos| !in_gap(tile_pos), &MAP_SIZE),
+            Some(TilePos { x: 0, y: 3 })
+        );
+
+        // On an empty map, the placement nearest to the center wins.
+        assert_eq!(
+            best_fit_rect(UVec2::new(2, 1), |_| false, &MAP_SIZE),
+            Some(TilePos { x: 2, y: 2 })
+        );
+
+        assert_eq!(best_fit_rect(footprint, |_| true, &MAP_SIZE), None);
+        assert_eq!(best_fit_rect(UVec2::new(0, 1), |_| false, &MAP_SIZE), None);
+        assert_eq!(best_fit_rect(UVec2::new(7, 1), |_| false, &MAP_SIZE), None);
+    }
+
+    #[test]
+    fn shuffled_tiles_are_deterministic_and_cover_the_map() {
+        let tiles = shuffled_tiles(&MAP_SIZE, 42);
+        assert_eq!(tiles, shuffled_tiles(&MAP_SIZE, 42));
+        assert_ne!(tiles, shuffled_tiles(&MAP_SIZE, 43));
+
+        let mut sorted = tiles.clone();
+        sorted.sort_by_key(|tile_pos| tile_pos.to_index(&MAP_SIZE));
+        let row_major: Vec<TilePos> = (0..MAP_SIZE.y)
+            .flat_map(|y| (0..MAP_SIZE.x).map(move |x| TilePos { x, y }))
+            .collect();
+        assert_eq!(sorted, row_major);
+        assert_ne!(tiles, row_major);
+    }
+}
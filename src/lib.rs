@@ -93,6 +93,9 @@ pub struct TilemapBundle {
 pub mod prelude {
     #[cfg(not(feature = "atlas"))]
     pub use crate::array_texture_preload::*;
+    pub use crate::helpers::biome::*;
+    pub use crate::helpers::bitset::*;
+    pub use crate::helpers::field::*;
     pub use crate::helpers::filling::*;
     pub use crate::helpers::geometry::*;
     pub use crate::helpers::neighbors::*;
@@ -27,6 +27,41 @@ impl TilePos {
         ((self.y * tilemap_size.x as u32) + self.x) as usize
     }
 
+    /// Converts an index in a flattened vector (1D) into a tile position (2D), assuming the
+    /// tile position lies in a tilemap of the specified size. This is the inverse of
+    /// [`to_index`](TilePos::to_index).
+    ///
+    /// Returns `None` if `index` lies beyond the last tile of the tilemap.
+    pub fn from_index(index: usize, tilemap_size: &TilemapSize) -> Option<Self> {
+        if index >= tilemap_size.count() {
+            return None;
+        }
+        let index = u32::try_from(index).ok()?;
+        Some(Self {
+            x: index % tilemap_size.x,
+            y: index / tilemap_size.x,
+        })
+    }
+
+    /// Rotates `self` by `pi` about the center of a tilemap of the specified size, returning
+    /// `(width - 1 - x, height - 1 - y)`.
+    ///
+    /// Reflecting twice gives back `self`. On a hex map using axial coordinates, this is also a
+    /// rotation by `pi` in axial space, so adjacency is preserved (see
+    /// [`AxialPos::point_reflect`](crate::helpers::hex_grid::axial::AxialPos::point_reflect)).
+    ///
+    /// `self` must lie within the tilemap. This is only checked in debug builds.
+    pub fn point_reflect(&self, map_size: &TilemapSize) -> TilePos {
+        debug_assert!(
+            self.within_map_bounds(map_size),
+            "{self:?} does not lie within a tilemap of size {map_size:?}"
+        );
+        TilePos {
+            x: map_size.x - 1 - self.x,
+            y: map_size.y - 1 - self.y,
+        }
+    }
+
     /// Checks to see if `self` lies within a tilemap of the specified size.
     pub fn within_map_bounds(&self, map_size: &TilemapSize) -> bool {
         self.x < map_size.x && self.y < map_size.y
@@ -118,3 +153,42 @@ pub struct AnimatedTile {
     /// The speed the animation plays back at.
     pub speed: f32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_index_inverts_to_index() {
+        let map_size = TilemapSize { x: 4, y: 3 };
+        for index in 0..map_size.count() {
+            let tile_pos = TilePos::from_index(index, &map_size).unwrap();
+            assert!(tile_pos.within_map_bounds(&map_size));
+            assert_eq!(tile_pos.to_index(&map_size), index);
+        }
+        assert_eq!(TilePos::from_index(map_size.count(), &map_size), None);
+        assert_eq!(TilePos::from_index(0, &TilemapSize { x: 0, y: 3 }), None);
+    }
+
+    #[test]
+    fn point_reflect_maps_corners_to_opposite_corners() {
+        let map_size = TilemapSize { x: 5, y: 3 };
+        let corner = TilePos { x: 0, y: 0 };
+        assert_eq!(corner.point_reflect(&map_size), TilePos { x: 4, y: 2 });
+
+        for y in 0..map_size.y {
+            for x in 0..map_size.x {
+                let tile_pos = TilePos { x, y };
+                let reflected = tile_pos.point_reflect(&map_size);
+                assert_eq!(reflected.point_reflect(&map_size), tile_pos);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn point_reflect_rejects_out_of_bounds_tiles() {
+        TilePos { x: 5, y: 0 }.point_reflect(&TilemapSize { x: 5, y: 3 });
+    }
+}